@@ -4,10 +4,17 @@ pub const GRAVITY: Vec2 = Vec2::new(0.0, 1000.0);
 pub const DRAG_COEFFICIENT: f32 = 0.003;
 pub const ZOOM: f32 = 2.0;
 
+pub const SLEEP_ENERGY: f32 = 5.0;
+pub const SLEEP_TIME: f32 = 1.0;
+// how much the submerged fraction of a sleeping shape has to change before water wakes it
+pub const SLEEP_SUBMERGED: f32 = 0.01;
+
 pub const DRAW_BOUNDING_BOX: bool = false;
 pub const DRAW_SPRINGS: bool = false;
 pub const DRAW_POINTS: bool = false;
 pub const DRAW_OUTLINE: bool = false;
 pub const DRAW_FRAME: bool = false;
+pub const DRAW_SLEEPING: bool = false;
+pub const DRAW_JOINTS: bool = false;
 pub const DRAW_TRIANGLES: bool = false;
 pub const DRAW_TEXTURE: bool = true;
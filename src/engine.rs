@@ -1,13 +1,39 @@
+mod collider;
 mod collision;
 mod components;
 mod drawing;
+mod field;
+mod fluid;
+mod geometry;
+mod grab;
+mod joint;
+mod matching;
 mod math;
 mod mesh;
+mod muscle;
+mod particles;
+mod query;
+mod sensor;
 mod shape;
+mod topology;
+mod world;
 
+pub use collider::*;
 pub use collision::*;
 pub use components::*;
 pub use drawing::*;
+pub use field::*;
+pub use fluid::*;
+pub use geometry::*;
+pub use grab::*;
+pub use joint::*;
+pub use matching::*;
 pub use math::*;
 pub use mesh::*;
+pub use muscle::*;
+pub use particles::*;
+pub use query::*;
+pub use sensor::*;
 pub use shape::*;
+pub use topology::*;
+pub use world::*;
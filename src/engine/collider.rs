@@ -0,0 +1,163 @@
+use macroquad::prelude::*;
+
+use super::*;
+
+// small gap left between a point and a thin collider so it stays on the side it came from
+const SKIN: f32 = 0.01;
+
+#[allow(dead_code)]
+pub enum Collider {
+  Segment(Vec2, Vec2),
+  // open polyline
+  Chain(Vec<Vec2>),
+  Box(Vec2, Vec2),
+  Circle(Vec2, f32),
+  Polygon(Vec<Vec2>),
+}
+
+impl Collider {
+  pub fn bounds(&self) -> (Vec2, Vec2) {
+    let fold = |points: &Vec<Vec2>| {
+      points.iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), p| (min.min(*p), max.max(*p)),
+      )
+    };
+
+    return match self {
+      Collider::Segment(a, b) => (a.min(*b), a.max(*b)),
+      Collider::Chain(points) => fold(points),
+      Collider::Box(min, max) => (*min, *max),
+      Collider::Circle(center, radius) => (
+        *center - Vec2::splat(*radius),
+        *center + Vec2::splat(*radius),
+      ),
+      Collider::Polygon(points) => fold(points),
+    };
+  }
+
+  // the direction and distance to push the point out, if it is within `radius` of the collider
+  // or crossed it since its previous position
+  pub fn contact(&self, point: &PointMass, radius: f32) -> Option<(Vec2, f32)> {
+    return match self {
+      Collider::Segment(a, b) => segment_contact(point, radius, *a, *b),
+      Collider::Chain(points) => points
+        .windows(2)
+        .find_map(|edge| segment_contact(point, radius, edge[0], edge[1])),
+      Collider::Box(min, max) => polygon_contact(
+        point.position,
+        radius,
+        &vec![*min, Vec2::new(max.x, min.y), *max, Vec2::new(min.x, max.y)],
+      ),
+      Collider::Circle(center, size) => {
+        let offset = point.position - *center;
+        let distance = offset.length();
+        if distance >= size + radius {
+          return None;
+        }
+
+        let normal = if distance > 0.0 {
+          offset / distance
+        } else {
+          Vec2::new(0.0, -1.0)
+        };
+        Some((normal, size + radius - distance))
+      }
+      Collider::Polygon(points) => polygon_contact(point.position, radius, points),
+    };
+  }
+
+  pub fn draw(&self) {
+    match self {
+      Collider::Segment(a, b) => draw_line_vec(*a, *b, 2.0, GRAY),
+      Collider::Chain(points) => {
+        for edge in points.windows(2) {
+          draw_line_vec(edge[0], edge[1], 2.0, GRAY);
+        }
+      }
+      Collider::Box(min, max) => draw_rectangle(min.x, min.y, max.x - min.x, max.y - min.y, GRAY),
+      Collider::Circle(center, radius) => draw_circle_vec(*center, *radius, GRAY),
+      Collider::Polygon(points) => {
+        for [a, b, c] in triangulation(points) {
+          draw_triangle(points[a], points[b], points[c], GRAY);
+        }
+
+        let mut prev = points.len() - 1;
+        for i in 0..points.len() {
+          draw_line_vec(points[prev], points[i], 2.0, GRAY);
+          prev = i;
+        }
+      }
+    }
+  }
+}
+
+// a point that moved across the segment is put back on the side it came from, one that is
+// only touching it is pushed out of the capsule around it
+fn segment_contact(point: &PointMass, radius: f32, a: Vec2, b: Vec2) -> Option<(Vec2, f32)> {
+  if segment_intersection(point.previous_position, point.position, a, b).is_none() {
+    return edge_contact(point.position, radius, a, b);
+  }
+
+  // treat whichever side the point came from as the outside
+  let side = if signed_distance(point.previous_position, a, b, 1.0) < 0.0 {
+    -1.0
+  } else {
+    1.0
+  };
+
+  return Some((
+    outward_normal(a, b, side),
+    -signed_distance(point.position, a, b, side) + radius + SKIN,
+  ));
+}
+
+fn edge_contact(position: Vec2, radius: f32, a: Vec2, b: Vec2) -> Option<(Vec2, f32)> {
+  let (on_edge, _) = closest_point_on_segment(position, a, b);
+  let distance = on_edge.distance(position);
+  if distance >= radius || distance == 0.0 {
+    return None;
+  }
+
+  return Some(((position - on_edge) / distance, radius - distance));
+}
+
+fn polygon_contact(position: Vec2, radius: f32, points: &Vec<Vec2>) -> Option<(Vec2, f32)> {
+  if !point_in_polygon(position, points) {
+    let mut prev = points.len() - 1;
+    let mut deepest: Option<(Vec2, f32)> = None;
+    for i in 0..points.len() {
+      if let Some(contact) = edge_contact(position, radius, points[prev], points[i]) {
+        if deepest.map_or(true, |(_, depth)| contact.1 > depth) {
+          deepest = Some(contact);
+        }
+      }
+
+      prev = i;
+    }
+
+    return deepest;
+  }
+
+  let mut closest = (points[0], points[0], f32::INFINITY);
+
+  let mut prev = points.len() - 1;
+  for i in 0..points.len() {
+    let (a, b) = (points[prev], points[i]);
+
+    let (on_edge, _) = closest_point_on_segment(position, a, b);
+    if on_edge.distance(position) < closest.2 {
+      closest = (a, b, on_edge.distance(position));
+    }
+
+    prev = i;
+  }
+
+  // push along the edge normal so points on or just past the boundary still go outwards
+  let (a, b, _) = closest;
+  let winding = polygon_winding(points);
+  return Some((
+    outward_normal(a, b, winding),
+    (-signed_distance(position, a, b, winding)).max(0.0) + radius + SKIN,
+  ));
+}
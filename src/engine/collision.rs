@@ -1,10 +1,9 @@
-use std::f32::INFINITY;
-
 use macroquad::prelude::*;
 
+use crate::config::*;
+
 use super::{math::*, Shape, *};
 
-const EPS: f32 = 0.00001;
 const FRICTION_COEFFICIENT: f32 = 0.75;
 
 pub struct Collision {
@@ -14,91 +13,408 @@ pub struct Collision {
   line: (usize, usize),
 }
 
+// a sleeping shape skips its update, so it is treated as static unless the body touching it is
+// moving, in which case it is woken first. waking resets the sleep timer, so shapes that are
+// already awake are left to the kinetic energy check
 pub fn shape_shape_collision(shape_a: &mut Shape, shape_b: &mut Shape) {
+  if shape_a.is_sleeping() && shape_b.is_sleeping() {
+    return;
+  }
+
+  // a one-way shape only blocks with its own edges, its vertices poking into a body passing
+  // through would shove it back out the way it came
+  if shape_a.one_way().is_some() && shape_b.one_way().is_none() {
+    return;
+  }
+
+  let mut sleeping_a = shape_a.is_sleeping();
+
   for point in shape_a.points.iter_mut() {
     if let Some(collision) = point_shape_collision(point.position, shape_b) {
+      if sleeping_a {
+        let line = collision.line;
+        if is_moving(&shape_b.points[line.0]) || is_moving(&shape_b.points[line.1]) {
+          sleeping_a = false;
+        } else {
+          resolve_line_static(shape_b, point, collision);
+          continue;
+        }
+      }
+
       resolve_point_line(point, shape_b, collision);
     }
   }
+
+  if shape_a.is_sleeping() && !sleeping_a {
+    shape_a.wake();
+  }
+}
+
+// catches edges crossing each other when neither has a vertex inside the other shape, like a
+// thin spike passing through a long edge
+pub fn edge_edge_collision(shape_a: &mut Shape, shape_b: &mut Shape) {
+  if shape_a.one_way().is_some() || shape_b.one_way().is_some() {
+    return;
+  }
+
+  let (min_a, max_a) = shape_a.bounding_box;
+  let (min_b, max_b) = shape_b.bounding_box;
+  if max_a.x < min_b.x || max_a.y < min_b.y || min_a.x > max_b.x || min_a.y > max_b.y {
+    return;
+  }
+
+  if shape_a.is_sleeping() && shape_b.is_sleeping() {
+    return;
+  }
+
+  let winding_a = outline_winding(shape_a);
+  let winding_b = outline_winding(shape_b);
+
+  let na = shape_a.points.len();
+  let nb = shape_b.points.len();
+
+  for i in 0..na {
+    let edge_a = ((i + na - 1) % na, i);
+
+    for j in 0..nb {
+      let edge_b = ((j + nb - 1) % nb, j);
+
+      let a0 = shape_a.points[edge_a.0].position;
+      let a1 = shape_a.points[edge_a.1].position;
+      let b0 = shape_b.points[edge_b.0].position;
+      let b1 = shape_b.points[edge_b.1].position;
+
+      if a0.max(a1).x < b0.min(b1).x
+        || a0.max(a1).y < b0.min(b1).y
+        || a0.min(a1).x > b0.max(b1).x
+        || a0.min(a1).y > b0.max(b1).y
+      {
+        continue;
+      }
+
+      let (s, t) = match segment_intersection(a0, a1, b0, b1) {
+        Some(params) => params,
+        None => continue,
+      };
+
+      // push out along whichever edge normal needs the smaller correction, n is the
+      // direction b has to move relative to a
+      let normal_a = outward_normal(a0, a1, winding_a);
+      let normal_b = outward_normal(b0, b1, winding_b);
+      let depth_b =
+        -signed_distance(b0, a0, a1, winding_a).min(signed_distance(b1, a0, a1, winding_a));
+      let depth_a =
+        -signed_distance(a0, b0, b1, winding_b).min(signed_distance(a1, b0, b1, winding_b));
+
+      let (n, depth) = if depth_b <= depth_a {
+        (normal_a, depth_b)
+      } else {
+        (-normal_b, depth_a)
+      };
+
+      if depth <= 0.0 {
+        continue;
+      }
+
+      wake_if_hit(shape_a, &shape_b.points, edge_b);
+      wake_if_hit(shape_b, &shape_a.points, edge_a);
+
+      let weight_a = if shape_a.is_sleeping() {
+        0.0
+      } else {
+        edge_inverse_mass(shape_a, edge_a, s)
+      };
+      let weight_b = if shape_b.is_sleeping() {
+        0.0
+      } else {
+        edge_inverse_mass(shape_b, edge_b, t)
+      };
+      let total = weight_a + weight_b;
+      if total == 0.0 {
+        continue;
+      }
+
+      let vel_a = lerp_vec(
+        s,
+        shape_a.points[edge_a.0].velocity,
+        shape_a.points[edge_a.1].velocity,
+      );
+      let vel_b = lerp_vec(
+        t,
+        shape_b.points[edge_b.0].velocity,
+        shape_b.points[edge_b.1].velocity,
+      );
+      let approach = (vel_b - vel_a).dot(n).min(0.0);
+
+      push_edge(
+        shape_a,
+        edge_a,
+        s,
+        -n * depth * weight_a / total,
+        n * approach * weight_a / total,
+      );
+      push_edge(
+        shape_b,
+        edge_b,
+        t,
+        n * depth * weight_b / total,
+        -n * approach * weight_b / total,
+      );
+    }
+  }
+}
+
+fn wake_if_hit(shape: &mut Shape, points: &Vec<PointMass>, edge: (usize, usize)) {
+  if shape.is_sleeping() && (is_moving(&points[edge.0]) || is_moving(&points[edge.1])) {
+    shape.wake();
+  }
+}
+
+fn is_moving(point: &PointMass) -> bool {
+  return 0.5 * point.mass * point.velocity.length_squared() > SLEEP_ENERGY;
+}
+
+pub fn outline_winding(shape: &Shape) -> f32 {
+  let outline = shape.points.iter().map(|p| p.position).collect();
+  return polygon_winding(&outline);
+}
+
+fn edge_inverse_mass(shape: &Shape, edge: (usize, usize), t: f32) -> f32 {
+  let mut total = 0.0;
+  for (i, w) in [(edge.0, 1.0 - t), (edge.1, t)] {
+    let point = &shape.points[i];
+    if !point.locked {
+      total += w * w / point.mass;
+    }
+  }
+
+  return total;
 }
 
-pub fn resolve_point_line(point: &mut PointMass, shape: &mut Shape, collision: Collision) {
+// moves the point at `t` along the edge by `dist`, spread over both ends by their weight.
+// callers leave sleeping shapes alone or wake them first
+fn push_edge(shape: &mut Shape, edge: (usize, usize), t: f32, dist: Vec2, velocity: Vec2) {
+  let total = edge_inverse_mass(shape, edge, t);
+  if total == 0.0 {
+    return;
+  }
+
+  for (i, w) in [(edge.0, 1.0 - t), (edge.1, t)] {
+    let point = &mut shape.points[i];
+    if point.locked {
+      continue;
+    }
+
+    let k = w / point.mass / total;
+    point.add_position(dist * k);
+    point.velocity += velocity * k;
+  }
+}
+
+// returns false when a one-way shape let the point through
+pub fn resolve_point_line(point: &mut PointMass, shape: &mut Shape, collision: Collision) -> bool {
+  if !one_way_blocks(point, shape, &collision) {
+    return false;
+  }
+
+  // a point resting on a sleeping shape bounces off it without waking it
+  if shape.is_sleeping() {
+    if !is_moving(point) {
+      resolve_point_static(point, collision.normal, collision.d);
+      return true;
+    }
+    shape.wake();
+  }
+
   let point_a = &shape.points[collision.line.0];
   let point_b = &shape.points[collision.line.1];
 
-  let l_vel = (1.0 - collision.t) * point_a.velocity + collision.t * point_b.velocity;
-  let p_vel = point.velocity;
+  let l_vel = if shape.is_kinematic() {
+    shape.surface_velocity(lerp_vec(collision.t, point_a.position, point_b.position))
+  } else {
+    (1.0 - collision.t) * point_a.velocity + collision.t * point_b.velocity
+  };
 
   let dist = collision.d * collision.normal;
 
-  let p_parallel = p_vel.dot(collision.normal);
-  let p_perpendicular = p_vel - (p_parallel * collision.normal);
   let l_parallel = l_vel.dot(collision.normal);
   let l_perpendicular = l_vel - (l_parallel * collision.normal);
 
   // v1 = ((m1-m2)v1 + 2m2v2)/(m1+m2)
   // v2 = ((m2-m1)v2 + 2m1v1)/(m1+m2)
-  let p_parallel = (-p_parallel + 4.0 * l_parallel) / 3.0;
+  let p_parallel = (-point.velocity.dot(collision.normal) + 4.0 * l_parallel) / 3.0;
   let l_parallel = (l_parallel + 2.0 * p_parallel) / 3.0;
 
   let l_vel_f = l_parallel * collision.normal + l_perpendicular * FRICTION_COEFFICIENT;
 
-  point.add_position(dist * 0.5);
-  point.velocity = p_parallel * collision.normal + p_perpendicular * FRICTION_COEFFICIENT;
+  // only kinematic surfaces carry points along with them, soft edges just slow them down
+  let surface_velocity = if shape.is_kinematic() {
+    l_vel
+  } else {
+    l_vel.dot(collision.normal) * collision.normal
+  };
+
+  if !point.locked {
+    point.add_position(dist * 0.5);
+    point.velocity = bounce_velocity(point.velocity, surface_velocity, collision.normal);
+  }
 
   let particle = &mut shape.points[collision.line.0];
-  particle.add_position((1.0 - collision.t) * -dist);
-  particle.velocity = (1.0 - collision.t) * l_vel_f;
+  if !particle.locked {
+    particle.add_position((1.0 - collision.t) * -dist);
+    particle.velocity = (1.0 - collision.t) * l_vel_f;
+  }
 
   let particle = &mut shape.points[collision.line.1];
-  particle.add_position(collision.t * -dist);
-  particle.velocity = collision.t * l_vel_f;
+  if !particle.locked {
+    particle.add_position(collision.t * -dist);
+    particle.velocity = collision.t * l_vel_f;
+  }
+
+  return true;
 }
 
-pub fn point_shape_collision(point: Vec2, shape: &Shape) -> Option<Collision> {
-  let (min, max) = shape.bounding_box;
-  if point.x < min.x || point.y < min.y || point.x > max.x || point.y > max.y {
-    return None;
+// like resolve_point_line, but the push and impulse are split by mass so light particles only
+// nudge the edge. sleeping shapes aren't woken, or rain would keep them awake forever
+pub fn resolve_particle_line(
+  point: &mut PointMass,
+  shape: &mut Shape,
+  collision: Collision,
+) -> bool {
+  if !one_way_blocks(point, shape, &collision) {
+    return false;
   }
 
-  let ray_end = Vec2::new(max.x + 5.0, point.y);
-  let np = shape.points.len();
-  let mut intersections = 0;
+  let (line, t, normal) = (collision.line, collision.t, collision.normal);
+
+  let weight_point = if point.locked { 0.0 } else { 1.0 / point.mass };
+  let weight_edge = if shape.is_kinematic() || shape.is_sleeping() {
+    0.0
+  } else {
+    edge_inverse_mass(shape, line, t)
+  };
+  let total = weight_point + weight_edge;
+  if total == 0.0 {
+    return true;
+  }
 
-  let mut prev_a = np - 1;
+  let point_a = &shape.points[line.0];
+  let point_b = &shape.points[line.1];
+  let edge_velocity = if shape.is_kinematic() {
+    shape.surface_velocity(lerp_vec(t, point_a.position, point_b.position))
+  } else {
+    lerp_vec(t, point_a.velocity, point_b.velocity)
+  };
+
+  let relative = point.velocity - edge_velocity;
+  let approach = relative.dot(normal);
+
+  let mut impulse = Vec2::ZERO;
+  if approach < 0.0 {
+    let tangent = relative - approach * normal;
+    impulse = (-(1.0 + point.restitution) * approach * normal
+      - tangent * (1.0 - FRICTION_COEFFICIENT))
+      / total;
+  }
 
-  for i in 0..np {
-    let a = shape.points[prev_a].position;
-    let b = shape.points[i].position;
+  point.add_position(normal * collision.d * weight_point / total);
+  point.velocity += impulse * weight_point;
+
+  if weight_edge > 0.0 {
+    push_edge(
+      shape,
+      line,
+      t,
+      -normal * collision.d * weight_edge / total,
+      -impulse * weight_edge,
+    );
+  }
 
-    if (a.x < point.x || a.x > ray_end.x) && (b.x < point.x || b.x > ray_end.x) {
-      prev_a = i;
-      continue;
-    }
+  return true;
+}
 
-    if (a.x < point.x || a.x > ray_end.x) || (b.x < point.x || b.x > ray_end.x) {
-      prev_a = i;
-      if let Some(_) = get_intersection_point((point, ray_end), (a, b)) {
-        intersections += 1;
-      }
-      continue;
-    }
+// pushes the edge off a point of a sleeping shape, which stays where it is
+fn resolve_line_static(shape: &mut Shape, point: &PointMass, collision: Collision) {
+  if !one_way_blocks(point, shape, &collision) {
+    return;
+  }
 
-    if (a.y >= point.y && b.y <= point.y) || (a.y <= point.y && b.y >= point.y) {
-      intersections += 1;
-    }
+  let (line, t, normal) = (collision.line, collision.t, collision.normal);
+  let velocity = lerp_vec(
+    t,
+    shape.points[line.0].velocity,
+    shape.points[line.1].velocity,
+  );
+  let approach = velocity.dot(normal).max(0.0);
 
-    prev_a = i;
+  push_edge(shape, line, t, -normal * collision.d, -normal * approach);
+}
+
+// pushes a point out of something that doesn't move, along `normal` by `depth`
+pub fn resolve_point_static(point: &mut PointMass, normal: Vec2, depth: f32) {
+  if point.locked {
+    return;
+  }
+
+  point.add_position(normal * depth);
+  point.velocity = bounce_velocity(point.velocity, Vec2::ZERO, normal);
+}
+
+// the point's velocity after hitting a surface moving at `surface_velocity`
+fn bounce_velocity(velocity: Vec2, surface_velocity: Vec2, normal: Vec2) -> Vec2 {
+  let p_parallel = velocity.dot(normal);
+  let p_perpendicular = velocity - (p_parallel * normal);
+  let l_parallel = surface_velocity.dot(normal);
+  let l_perpendicular = surface_velocity - (l_parallel * normal);
+
+  let p_parallel = (-p_parallel + 4.0 * l_parallel) / 3.0;
+
+  return p_parallel * normal
+    + l_perpendicular
+    + (p_perpendicular - l_perpendicular) * FRICTION_COEFFICIENT;
+}
+
+fn one_way_blocks(point: &PointMass, shape: &Shape, collision: &Collision) -> bool {
+  let (direction, max_angle) = match shape.one_way() {
+    Some(one_way) => one_way,
+    None => return true,
+  };
+
+  if point.drop_through {
+    return false;
+  }
+
+  if collision.normal.dot(direction) < max_angle.cos() {
+    return false;
+  }
+
+  let line_velocity = lerp_vec(
+    collision.t,
+    shape.points[collision.line.0].velocity,
+    shape.points[collision.line.1].velocity,
+  );
+  if (point.velocity - line_velocity).dot(direction) > 0.0 {
+    return false;
+  }
+
+  // points that were already inside last step are on their way through
+  return point_shape_collision(point.previous_position, shape).is_none();
+}
+
+pub fn point_shape_collision(point: Vec2, shape: &Shape) -> Option<Collision> {
+  let (min, max) = shape.bounding_box;
+  if point.x < min.x || point.y < min.y || point.x > max.x || point.y > max.y {
+    return None;
   }
 
-  if intersections % 2 == 0 {
+  let outline = shape.points.iter().map(|p| p.position).collect();
+  if !point_in_polygon(point, &outline) {
     return None;
   }
 
-  let mut closest_d = INFINITY;
-  let mut closest_point = Vec2::ZERO;
-  let mut closest_line = (Vec2::ZERO, Vec2::ZERO, 0, 0);
-  let mut closest_n = Vec2::ZERO;
+  let winding = polygon_winding(&outline);
+  let np = shape.points.len();
+  let mut closest: Option<Collision> = None;
 
   let mut prev_a = np - 1;
 
@@ -106,70 +422,106 @@ pub fn point_shape_collision(point: Vec2, shape: &Shape) -> Option<Collision> {
     let a = shape.points[prev_a].position;
     let b = shape.points[i].position;
 
-    let (close_point, d, n) = closest_point_on_line(point, (b, a));
+    let (close_point, t) = closest_point_on_segment(point, b, a);
+    let d = close_point.distance(point);
 
-    // draw_circle_vec(close_point, 5.0, BLUE);
-    if d < closest_d {
-      closest_d = d;
-      closest_point = close_point;
-      closest_line = (b, a, i, prev_a);
-      closest_n = n;
+    if closest.as_ref().map_or(true, |c| d < c.d) {
+      closest = Some(Collision {
+        d,
+        normal: outward_normal(a, b, winding),
+        t,
+        line: (i, prev_a),
+      });
     }
 
     prev_a = i;
   }
-  // draw_circle_vec(closest_point, 5.0, RED);
 
-  let line_t = inverse_lerp_vec(closest_point, closest_line.0, closest_line.1);
-
-  return Some(Collision {
-    d: closest_d + 1.0,
-    normal: closest_n,
-    t: line_t,
-    line: (closest_line.2, closest_line.3),
+  return closest.map(|collision| Collision {
+    d: collision.d + 1.0,
+    ..collision
   });
 }
 
-fn get_intersection_point(ray: (Vec2, Vec2), line: (Vec2, Vec2)) -> Option<Vec2> {
-  let (a, b) = line;
-  let (c, d) = ray;
-
-  let denominator = (d.x - c.x) * (b.y - a.y) - (b.x - a.x) * (d.y - c.y);
+// like point_shape_collision, but for a point with a radius, so it also collides with edges
+// it is touching from outside
+pub fn circle_shape_collision(point: &PointMass, shape: &Shape) -> Option<Collision> {
+  let radius = point.radius();
 
-  let r = ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)) / denominator;
-  if (r + EPS) < 0.0 {
-    return None;
+  if let Some(collision) = point_shape_collision(point.position, shape) {
+    return Some(Collision {
+      d: collision.d + radius,
+      ..collision
+    });
   }
 
-  let s = ((a.x - c.x) * (d.y - c.y) - (d.x - c.x) * (a.y - c.y)) / denominator;
-  if (s + EPS) < 0.0 || (s - EPS) > 1.0 {
+  let (min, max) = shape.bounding_box;
+  let position = point.position;
+  if position.x < min.x - radius
+    || position.y < min.y - radius
+    || position.x > max.x + radius
+    || position.y > max.y + radius
+  {
     return None;
   }
 
-  return Some(Vec2 {
-    x: s * (b.x - a.x) + a.x,
-    y: s * (b.y - a.y) + a.y,
-  });
-}
+  let np = shape.points.len();
+  let mut closest: Option<Collision> = None;
+  let mut closest_distance = radius;
 
-pub fn closest_point_on_line(point: Vec2, line: (Vec2, Vec2)) -> (Vec2, f32, Vec2) {
-  let dv = (line.1 - line.0).normalize();
-  let (dx, dy) = (dv.x, dv.y);
+  let mut prev = np - 1;
+  for i in 0..np {
+    let (on_edge, t) = closest_point_on_segment(
+      position,
+      shape.points[prev].position,
+      shape.points[i].position,
+    );
+    let distance = on_edge.distance(position);
+
+    if distance < closest_distance && distance > 0.0 {
+      closest_distance = distance;
+      closest = Some(Collision {
+        d: radius - distance,
+        normal: (position - on_edge) / distance,
+        t,
+        line: (prev, i),
+      });
+    }
 
-  let n = Vec2::new(-dy, dx);
-  let m = dy / dx;
-  let b = line.0.y - m * line.0.x;
+    prev = i;
+  }
 
-  let d = (m * point.x - point.y + b) / (n.y - m * n.x);
-  let close_point = d * n + point;
+  return closest;
+}
 
-  let t = inverse_lerp_vec(close_point, line.0, line.1);
+// pushes two overlapping points apart and exchanges momentum along the line between them
+pub fn point_point_collision(a: &mut PointMass, b: &mut PointMass) {
+  let offset = b.position - a.position;
+  let distance = offset.length();
+  let radius = a.radius() + b.radius();
+  if distance >= radius || distance == 0.0 {
+    return;
+  }
 
-  if t <= 1.0 && t >= 0.0 {
-    return (close_point, d.abs(), n);
+  let weight_a = if a.locked { 0.0 } else { 1.0 / a.mass };
+  let weight_b = if b.locked { 0.0 } else { 1.0 / b.mass };
+  let total = weight_a + weight_b;
+  if total == 0.0 {
+    return;
   }
-  if t > 1.0 {
-    return (line.1, point.distance(line.1), n);
+
+  let normal = offset / distance;
+  let depth = radius - distance;
+  a.position -= normal * depth * weight_a / total;
+  b.position += normal * depth * weight_b / total;
+
+  let approach = (b.velocity - a.velocity).dot(normal);
+  if approach >= 0.0 {
+    return;
   }
-  return (line.0, point.distance(line.0), n);
+
+  let restitution = (a.restitution + b.restitution) * 0.5;
+  let impulse = -(1.0 + restitution) * approach / total;
+  a.velocity -= normal * impulse * weight_a;
+  b.velocity += normal * impulse * weight_b;
 }
@@ -1,15 +1,36 @@
-use std::f32::consts::PI;
+use std::f32::consts::{PI, TAU};
 
 use macroquad::prelude::*;
 
-use super::inverse_lerp_f32;
+use crate::config::*;
+
+use super::{cross_2d, inverse_lerp_f32};
+
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+pub enum Drag {
+  Quadratic(f32),
+  Linear(f32),
+}
 
 pub struct PointMass {
   pub locked: bool,
   pub mass: f32,
   pub diameter: f32,
   pub position: Vec2,
+  pub previous_position: Vec2,
   pub velocity: Vec2,
+  pub gravity_scale: f32,
+  pub gravity_direction: Option<Vec2>,
+  pub drag: Drag,
+  pub category: u32,
+  pub mask: u32,
+  // falls through one-way shapes while set
+  pub drop_through: bool,
+  // 0 for no bounce, 1 for a perfectly elastic one against other points
+  pub restitution: f32,
+  // collision size, separate from the diameter that drag uses
+  radius: Option<f32>,
 }
 
 pub struct Spring {
@@ -20,6 +41,15 @@ pub struct Spring {
   pub b: usize,
 }
 
+pub struct AngleSpring {
+  strength: f32,
+  angle: f32,
+  damping: f32,
+  pub a: usize,
+  pub b: usize,
+  pub c: usize,
+}
+
 impl PointMass {
   pub fn new(position: Vec2, mass: f32, locked: bool) -> Self {
     return Self {
@@ -27,11 +57,47 @@ impl PointMass {
       mass,
       diameter: 2.0 * (mass / PI).sqrt(),
       position: position,
+      previous_position: position,
       velocity: Vec2::ZERO,
+      gravity_scale: 1.0,
+      gravity_direction: None,
+      drag: Drag::Quadratic(DRAG_COEFFICIENT),
+      category: 1,
+      mask: u32::MAX,
+      drop_through: false,
+      restitution: 0.5,
+      radius: None,
+    };
+  }
+
+  // the point's share of the world's gravity
+  pub fn gravity(&self, gravity: Vec2) -> Vec2 {
+    let gravity = match self.gravity_direction {
+      Some(direction) => direction.normalize_or_zero() * gravity.length(),
+      None => gravity,
+    };
+
+    return gravity * self.gravity_scale;
+  }
+
+  pub fn apply_drag(&mut self, scale: f32, delta_time: f32) {
+    let speed = self.velocity.length();
+    if speed == 0.0 {
+      return;
+    }
+
+    let drag = match self.drag {
+      Drag::Quadratic(coefficient) => {
+        coefficient * self.diameter * speed * speed * -self.velocity.normalize()
+      }
+      Drag::Linear(coefficient) => coefficient * self.diameter * -self.velocity,
     };
+
+    self.apply_force(drag * scale * delta_time);
   }
 
   pub fn update(&mut self, delta_time: f32) {
+    self.previous_position = self.position;
     if self.locked {
       return;
     }
@@ -39,19 +105,45 @@ impl PointMass {
     self.position += self.velocity * delta_time;
   }
 
+  #[allow(dead_code)]
+  pub fn set_mass(&mut self, mass: f32) {
+    self.mass = mass;
+    self.diameter = 2.0 * (mass / PI).sqrt();
+  }
+
   pub fn add_position(&mut self, dist: Vec2) {
+    if self.locked {
+      return;
+    }
+
     self.position += dist;
   }
 
+  pub fn radius(&self) -> f32 {
+    return self.radius.unwrap_or(self.diameter * 0.5);
+  }
+
+  pub fn set_radius(&mut self, radius: f32) {
+    self.radius = Some(radius);
+  }
+
   pub fn draw(&self) {
-    draw_circle(self.position.x, self.position.y, 5.0, WHITE);
+    draw_circle(self.position.x, self.position.y, self.radius(), WHITE);
   }
 
   pub fn apply_force(&mut self, force: Vec2) {
+    if self.locked {
+      return;
+    }
+
     self.velocity += force / self.mass;
   }
 
   pub fn apply_gravity(&mut self, force: Vec2) {
+    if self.locked {
+      return;
+    }
+
     self.velocity += force;
   }
 }
@@ -67,6 +159,22 @@ impl Spring {
     };
   }
 
+  pub fn length(&self) -> f32 {
+    return self.length;
+  }
+
+  pub fn set_length(&mut self, length: f32) {
+    self.length = length;
+  }
+
+  pub fn strength(&self) -> f32 {
+    return self.strength;
+  }
+
+  pub fn set_strength(&mut self, strength: f32) {
+    self.strength = strength;
+  }
+
   pub fn calculate_force(&self, point_a: &PointMass, point_b: &PointMass) -> Vec2 {
     let dist = (point_b.position - point_a.position).length();
 
@@ -100,3 +208,52 @@ impl Spring {
     );
   }
 }
+
+impl AngleSpring {
+  pub fn new(strength: f32, angle: f32, damping: f32, a: usize, b: usize, c: usize) -> Self {
+    return Self {
+      strength,
+      angle,
+      damping,
+      a,
+      b,
+      c,
+    };
+  }
+
+  pub fn angle(point_a: Vec2, point_b: Vec2, point_c: Vec2) -> f32 {
+    let u = point_a - point_b;
+    let v = point_c - point_b;
+
+    return cross_2d(u, v).atan2(u.dot(v));
+  }
+
+  // forces on a, b and c that turn the angle at b back towards its rest angle
+  pub fn calculate_forces(
+    &self,
+    point_a: &PointMass,
+    point_b: &PointMass,
+    point_c: &PointMass,
+  ) -> (Vec2, Vec2, Vec2) {
+    let u = point_a.position - point_b.position;
+    let v = point_c.position - point_b.position;
+
+    if u.length_squared() == 0.0 || v.length_squared() == 0.0 {
+      return (Vec2::ZERO, Vec2::ZERO, Vec2::ZERO);
+    }
+
+    let angle = Self::angle(point_a.position, point_b.position, point_c.position);
+    let error = (angle - self.angle + PI).rem_euclid(TAU) - PI;
+
+    let grad_a = -Vec2::new(-u.y, u.x) / u.length_squared();
+    let grad_c = Vec2::new(-v.y, v.x) / v.length_squared();
+    let grad_b = -(grad_a + grad_c);
+
+    let rate =
+      grad_a.dot(point_a.velocity) + grad_b.dot(point_b.velocity) + grad_c.dot(point_c.velocity);
+
+    let torque = -(self.strength * error + self.damping * rate);
+
+    return (grad_a * torque, grad_b * torque, grad_c * torque);
+  }
+}
@@ -0,0 +1,148 @@
+use macroquad::prelude::*;
+
+use super::{value_noise, PointMass};
+
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+pub enum Falloff {
+  Constant,
+  Linear,
+  InverseSquare,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+pub enum FieldKind {
+  Uniform(Vec2),
+  Attractor {
+    center: Vec2,
+    strength: f32,
+    radius: f32,
+    falloff: Falloff,
+  },
+  Vortex {
+    center: Vec2,
+    strength: f32,
+    radius: f32,
+    falloff: Falloff,
+  },
+  Wind {
+    direction: Vec2,
+    strength: f32,
+    turbulence: f32,
+    scale: f32,
+    speed: f32,
+  },
+  Explosion {
+    center: Vec2,
+    impulse: f32,
+    radius: f32,
+  },
+}
+
+pub struct ForceField {
+  pub kind: FieldKind,
+  pub region: Option<(Vec2, Vec2)>,
+}
+
+impl ForceField {
+  pub fn new(kind: FieldKind, region: Option<(Vec2, Vec2)>) -> Self {
+    return Self { kind, region };
+  }
+
+  pub fn is_one_shot(&self) -> bool {
+    return matches!(self.kind, FieldKind::Explosion { .. });
+  }
+
+  pub fn in_range(&self, position: Vec2) -> bool {
+    if let Some((min, max)) = self.region {
+      if position.x < min.x || position.y < min.y || position.x > max.x || position.y > max.y {
+        return false;
+      }
+    }
+
+    return match self.kind {
+      FieldKind::Attractor { center, radius, .. }
+      | FieldKind::Vortex { center, radius, .. }
+      | FieldKind::Explosion { center, radius, .. } => center.distance(position) <= radius,
+      _ => true,
+    };
+  }
+
+  // uniform, attractor and vortex fields are accelerations, wind is a force and explosions
+  // are a single impulse, so heavier points are pushed around less by the latter two
+  pub fn apply(&self, point: &mut PointMass, time: f32, delta_time: f32) {
+    if point.locked || !self.in_range(point.position) {
+      return;
+    }
+
+    match self.kind {
+      FieldKind::Uniform(acceleration) => {
+        point.apply_gravity(acceleration * delta_time);
+      }
+      FieldKind::Attractor {
+        center,
+        strength,
+        radius,
+        falloff,
+      } => {
+        let r = center - point.position;
+        if r.length() > 0.0 {
+          let scale = falloff_scale(falloff, r.length(), radius);
+          point.apply_gravity(r.normalize() * strength * scale * delta_time);
+        }
+      }
+      FieldKind::Vortex {
+        center,
+        strength,
+        radius,
+        falloff,
+      } => {
+        let r = point.position - center;
+        if r.length() > 0.0 {
+          let scale = falloff_scale(falloff, r.length(), radius);
+          let tangent = Vec2::new(-r.y, r.x).normalize();
+          point.apply_gravity(tangent * strength * scale * delta_time);
+        }
+      }
+      FieldKind::Wind {
+        direction,
+        strength,
+        turbulence,
+        scale,
+        speed,
+      } => {
+        let sample = point.position * scale;
+        let gust = value_noise(sample.x + time * speed, sample.y);
+        let sway = value_noise(sample.x - 31.7, sample.y + time * speed);
+
+        let perpendicular = Vec2::new(-direction.y, direction.x);
+        let force = direction * strength * (1.0 + turbulence * gust)
+          + perpendicular * strength * turbulence * sway;
+        point.apply_force(force * delta_time);
+      }
+      FieldKind::Explosion {
+        center,
+        impulse,
+        radius,
+      } => {
+        let r = point.position - center;
+        if r.length() > 0.0 {
+          let scale = falloff_scale(Falloff::Linear, r.length(), radius);
+          point.apply_force(r.normalize() * impulse * scale);
+        }
+      }
+    }
+  }
+}
+
+fn falloff_scale(falloff: Falloff, distance: f32, radius: f32) -> f32 {
+  return match falloff {
+    Falloff::Constant => 1.0,
+    Falloff::Linear => (1.0 - distance / radius).max(0.0),
+    Falloff::InverseSquare => {
+      let d = (distance / radius).max(0.1);
+      1.0 / (d * d)
+    }
+  };
+}
@@ -0,0 +1,190 @@
+use macroquad::prelude::*;
+
+use super::*;
+
+#[allow(dead_code)]
+pub enum FluidArea {
+  Box(Vec2, Vec2),
+  // must be convex
+  Polygon(Vec<Vec2>),
+}
+
+pub struct Fluid {
+  pub area: FluidArea,
+  pub density: f32,
+  pub linear_drag: f32,
+  pub angular_drag: f32,
+  surface: Option<fn(f32, f32) -> f32>,
+}
+
+impl Fluid {
+  pub fn new(area: FluidArea, density: f32, linear_drag: f32, angular_drag: f32) -> Self {
+    return Self {
+      area,
+      density,
+      linear_drag,
+      angular_drag,
+      surface: None,
+    };
+  }
+
+  // the surface function maps (x, time) to how far the water at x sits below the top of the area
+  pub fn set_surface(&mut self, surface: fn(f32, f32) -> f32) {
+    self.surface = Some(surface);
+  }
+
+  pub fn submerged(&self, polygon: &Vec<Vec2>, time: f32) -> Option<(f32, Vec2)> {
+    let mut clipped = polygon.clone();
+
+    match &self.area {
+      FluidArea::Box(min, max) => {
+        clipped = clip_polygon(&clipped, *min, Vec2::new(-1.0, 0.0));
+        clipped = clip_polygon(&clipped, *max, Vec2::new(1.0, 0.0));
+        clipped = clip_polygon(&clipped, *min, Vec2::new(0.0, -1.0));
+        clipped = clip_polygon(&clipped, *max, Vec2::new(0.0, 1.0));
+      }
+      FluidArea::Polygon(area) => {
+        let winding = polygon_area(area).signum();
+        let mut prev = area.len() - 1;
+        for i in 0..area.len() {
+          let edge = area[i] - area[prev];
+          let normal = Vec2::new(edge.y, -edge.x) * winding;
+          clipped = clip_polygon(&clipped, area[i], normal);
+          prev = i;
+        }
+      }
+    }
+
+    if let Some(surface) = self.surface {
+      if clipped.len() >= 3 {
+        let top = self.bounds().0.y;
+        let left = clipped.iter().fold(f32::INFINITY, |m, p| m.min(p.x));
+        let right = clipped.iter().fold(f32::NEG_INFINITY, |m, p| m.max(p.x));
+        let a = Vec2::new(left, top + surface(left, time));
+        let b = Vec2::new(right + 1.0, top + surface(right + 1.0, time));
+
+        let edge = b - a;
+        let mut normal = Vec2::new(edge.y, -edge.x);
+        if normal.y > 0.0 {
+          normal = -normal;
+        }
+        clipped = clip_polygon(&clipped, a, normal);
+      }
+    }
+
+    if clipped.len() < 3 {
+      return None;
+    }
+
+    let area = polygon_area(&clipped);
+    if area.abs() < f32::EPSILON {
+      return None;
+    }
+
+    return Some((area.abs(), polygon_centroid(&clipped)));
+  }
+
+  pub fn submerged_fraction(&self, shape: &Shape, time: f32) -> f32 {
+    return self
+      .submerged_shape(shape, time)
+      .map_or(0.0, |(fraction, _, _)| fraction);
+  }
+
+  // the fraction of the shape that is underwater, the submerged area and its centroid
+  fn submerged_shape(&self, shape: &Shape, time: f32) -> Option<(f32, f32, Vec2)> {
+    let (min, max) = shape.bounding_box;
+    let (area_min, area_max) = self.bounds();
+    if max.x < area_min.x || max.y < area_min.y || min.x > area_max.x || min.y > area_max.y {
+      return None;
+    }
+
+    let outline = shape.points.iter().map(|p| p.position).collect();
+    let shape_area = polygon_area(&outline).abs();
+
+    let (area, centroid) = self.submerged(&outline, time)?;
+    return Some(((area / shape_area).min(1.0), area, centroid));
+  }
+
+  // returns the fraction of the shape that is underwater
+  pub fn apply(&self, shape: &mut Shape, gravity: Vec2, time: f32, delta_time: f32) -> f32 {
+    if let Some((fraction, area, centroid)) = self.submerged_shape(shape, time) {
+      shape.apply_force_at(-gravity * self.density * area * delta_time, centroid);
+
+      let velocity = shape.velocity();
+      let angular_velocity = shape.angular_velocity();
+      shape.apply_force_at(
+        -velocity * self.linear_drag * fraction * shape.mass() * delta_time,
+        shape.center_of_mass(),
+      );
+      shape.apply_torque(
+        -angular_velocity * self.angular_drag * fraction * shape.moment_of_inertia() * delta_time,
+      );
+
+      return fraction;
+    }
+
+    return 0.0;
+  }
+
+  pub fn bounds(&self) -> (Vec2, Vec2) {
+    return match &self.area {
+      FluidArea::Box(min, max) => (*min, *max),
+      FluidArea::Polygon(area) => area.iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), p| (min.min(*p), max.max(*p)),
+      ),
+    };
+  }
+
+  pub fn draw(&self, time: f32) {
+    let color = Color::new(0.2, 0.45, 0.9, 0.35);
+
+    match &self.area {
+      FluidArea::Box(min, max) => {
+        let steps = ((max.x - min.x) / 10.0).ceil().max(1.0) as usize;
+        let width = (max.x - min.x) / steps as f32;
+
+        for i in 0..steps {
+          let x = min.x + i as f32 * width;
+          let top = match self.surface {
+            Some(surface) => (min.y + surface(x + width * 0.5, time)).clamp(min.y, max.y),
+            None => min.y,
+          };
+          draw_rectangle(x, top, width, max.y - top, color);
+        }
+      }
+      FluidArea::Polygon(area) => {
+        for i in 1..area.len() - 1 {
+          draw_triangle(area[0], area[i], area[i + 1], color);
+        }
+      }
+    }
+  }
+}
+
+// keeps the part of the polygon behind the plane through `origin` facing `normal`
+fn clip_polygon(polygon: &Vec<Vec2>, origin: Vec2, normal: Vec2) -> Vec<Vec2> {
+  let mut clipped = Vec::new();
+  if polygon.is_empty() {
+    return clipped;
+  }
+
+  let mut prev = polygon[polygon.len() - 1];
+  let mut prev_d = (prev - origin).dot(normal);
+
+  for point in polygon.iter() {
+    let d = (*point - origin).dot(normal);
+
+    if (d <= 0.0) != (prev_d <= 0.0) {
+      clipped.push(lerp_vec(prev_d / (prev_d - d), prev, *point));
+    }
+    if d <= 0.0 {
+      clipped.push(*point);
+    }
+
+    prev = *point;
+    prev_d = d;
+  }
+
+  return clipped;
+}
@@ -0,0 +1,279 @@
+use macroquad::prelude::*;
+
+use super::{cross_2d, lerp_vec, polygon_area};
+
+// how close to an edge, relative to its length, a point has to be to count as on it
+const ON_EDGE_TOLERANCE: f32 = 0.0001;
+const PARALLEL_EPS: f32 = 0.00001;
+
+// nonzero winding test, points on an edge or vertex count as inside and zero length edges
+// are ignored
+pub fn point_in_polygon(point: Vec2, polygon: &Vec<Vec2>) -> bool {
+  if polygon.len() < 3 {
+    return false;
+  }
+
+  let mut winding = 0;
+  let mut prev = polygon.len() - 1;
+
+  for i in 0..polygon.len() {
+    let a = polygon[prev];
+    let b = polygon[i];
+    prev = i;
+
+    if point_on_segment(point, a, b) {
+      return true;
+    }
+
+    // each edge counts on the half-open range [a.y, b.y) so a vertex level with the point
+    // is only counted once
+    let side = cross_2d(b - a, point - a);
+    if a.y <= point.y {
+      if b.y > point.y && side > 0.0 {
+        winding += 1;
+      }
+    } else if b.y <= point.y && side < 0.0 {
+      winding -= 1;
+    }
+  }
+
+  return winding != 0;
+}
+
+pub fn point_on_segment(point: Vec2, a: Vec2, b: Vec2) -> bool {
+  let edge = b - a;
+  let length_squared = edge.length_squared();
+  if length_squared == 0.0 {
+    return point == a;
+  }
+
+  let tolerance = ON_EDGE_TOLERANCE * length_squared;
+  if cross_2d(edge, point - a).abs() > tolerance {
+    return false;
+  }
+
+  let t = (point - a).dot(edge);
+  return t >= -tolerance && t <= length_squared + tolerance;
+}
+
+pub fn point_in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+  let ab = cross_2d(b - a, p - a);
+  let bc = cross_2d(c - b, p - b);
+  let ca = cross_2d(a - c, p - c);
+
+  return (ab >= 0.0 && bc >= 0.0 && ca >= 0.0) || (ab <= 0.0 && bc <= 0.0 && ca <= 0.0);
+}
+
+// 1 or -1 depending on which way the outline goes round, 0 if it has no area
+pub fn polygon_winding(polygon: &Vec<Vec2>) -> f32 {
+  let area = polygon_area(polygon);
+  if area == 0.0 {
+    return 0.0;
+  }
+
+  return area.signum();
+}
+
+// unit normal of the edge from a to b pointing out of a polygon with the given winding
+pub fn outward_normal(a: Vec2, b: Vec2, winding: f32) -> Vec2 {
+  let edge = b - a;
+  return Vec2::new(edge.y, -edge.x).normalize_or_zero() * winding;
+}
+
+// distance from the line through a and b, positive on the outside of the polygon
+pub fn signed_distance(point: Vec2, a: Vec2, b: Vec2, winding: f32) -> f32 {
+  return (point - a).dot(outward_normal(a, b, winding));
+}
+
+// where the point projects onto the line through a and b, 0 at a and 1 at b
+pub fn segment_parameter(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+  let edge = b - a;
+  let length_squared = edge.length_squared();
+  if length_squared == 0.0 {
+    return 0.0;
+  }
+
+  return (point - a).dot(edge) / length_squared;
+}
+
+// closest point to `point` on the segment, and how far along the segment it is
+pub fn closest_point_on_segment(point: Vec2, a: Vec2, b: Vec2) -> (Vec2, f32) {
+  let t = segment_parameter(point, a, b).clamp(0.0, 1.0);
+  return (lerp_vec(t, a, b), t);
+}
+
+// where two segments cross, as the fraction along each of them
+pub fn segment_intersection(a0: Vec2, a1: Vec2, b0: Vec2, b1: Vec2) -> Option<(f32, f32)> {
+  let r = a1 - a0;
+  let q = b1 - b0;
+
+  let denominator = cross_2d(r, q);
+  if denominator.abs() < PARALLEL_EPS * r.length() * q.length() || denominator == 0.0 {
+    return None;
+  }
+
+  let s = cross_2d(b0 - a0, q) / denominator;
+  let t = cross_2d(b0 - a0, r) / denominator;
+  if s < 0.0 || s > 1.0 || t < 0.0 || t > 1.0 {
+    return None;
+  }
+
+  return Some((s, t));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::object_data::{_SKRUNGLE_POINTS, PLATFORM_POINTS};
+
+  fn outlines() -> Vec<Vec<Vec2>> {
+    return vec![
+      PLATFORM_POINTS.iter().map(|p| p.0).collect(),
+      _SKRUNGLE_POINTS.iter().map(|p| p.0).collect(),
+    ];
+  }
+
+  fn edges(outline: &Vec<Vec2>) -> Vec<(Vec2, Vec2)> {
+    let mut edges = Vec::new();
+    let mut prev = outline.len() - 1;
+    for i in 0..outline.len() {
+      edges.push((outline[prev], outline[i]));
+      prev = i;
+    }
+    return edges;
+  }
+
+  #[test]
+  fn vertices_and_edges_are_inside() {
+    for outline in outlines() {
+      for (a, b) in edges(&outline) {
+        assert!(point_in_polygon(a, &outline), "vertex {a}");
+        assert!(
+          point_in_polygon((a + b) * 0.5, &outline),
+          "midpoint of {a} {b}"
+        );
+        assert!(
+          point_in_polygon(a.lerp(b, 0.25), &outline),
+          "quarter of {a} {b}"
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn points_just_off_edges() {
+    for outline in outlines() {
+      let winding = polygon_area(&outline).signum();
+
+      for (a, b) in edges(&outline) {
+        let edge = b - a;
+        let outward = Vec2::new(edge.y, -edge.x).normalize() * winding;
+        let middle = (a + b) * 0.5;
+
+        assert!(
+          point_in_polygon(middle - outward * 0.5, &outline),
+          "inside {a} {b}"
+        );
+        assert!(
+          !point_in_polygon(middle + outward * 0.5, &outline),
+          "outside {a} {b}"
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn level_with_vertices() {
+    for outline in outlines() {
+      let min = outline
+        .iter()
+        .fold(Vec2::splat(f32::INFINITY), |m, p| m.min(*p));
+      let max = outline
+        .iter()
+        .fold(Vec2::splat(f32::NEG_INFINITY), |m, p| m.max(*p));
+
+      for vertex in outline.iter() {
+        assert!(!point_in_polygon(
+          Vec2::new(min.x - 10.0, vertex.y),
+          &outline
+        ));
+        assert!(!point_in_polygon(
+          Vec2::new(max.x + 10.0, vertex.y),
+          &outline
+        ));
+      }
+    }
+  }
+
+  #[test]
+  fn horizontal_edges() {
+    for outline in outlines() {
+      let winding = polygon_area(&outline).signum();
+
+      for (a, b) in edges(&outline) {
+        if a.y != b.y || a.x == b.x {
+          continue;
+        }
+
+        let middle = (a + b) * 0.5;
+        let outward = Vec2::new(0.0, (a.x - b.x).signum() * winding);
+
+        assert!(point_in_polygon(middle, &outline));
+        assert!(point_in_polygon(middle - outward * 0.5, &outline));
+        assert!(!point_in_polygon(middle + outward * 0.5, &outline));
+      }
+    }
+  }
+
+  #[test]
+  fn matches_either_winding() {
+    for outline in outlines() {
+      let reversed = outline.iter().rev().cloned().collect::<Vec<Vec2>>();
+
+      for (a, b) in edges(&outline) {
+        let sample = (a + b) * 0.5 + Vec2::new(0.3, -0.7);
+        assert_eq!(
+          point_in_polygon(sample, &outline),
+          point_in_polygon(sample, &reversed)
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn degenerate_edges() {
+    for outline in outlines() {
+      let mut doubled = Vec::new();
+      for point in outline.iter() {
+        doubled.push(*point);
+        doubled.push(*point);
+      }
+
+      for (a, b) in edges(&outline) {
+        for sample in [(a + b) * 0.5, (a + b) * 0.5 + Vec2::new(0.3, -0.7)] {
+          assert_eq!(
+            point_in_polygon(sample, &outline),
+            point_in_polygon(sample, &doubled)
+          );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn vertical_segments() {
+    let (a, b) = (Vec2::new(10.0, 0.0), Vec2::new(10.0, 20.0));
+
+    let (closest, t) = closest_point_on_segment(Vec2::new(3.0, 5.0), a, b);
+    assert_eq!(closest, Vec2::new(10.0, 5.0));
+    assert_eq!(t, 0.25);
+    assert_eq!(
+      crate::engine::inverse_lerp_vec(Vec2::new(10.0, 15.0), a, b),
+      0.75
+    );
+    assert_eq!(signed_distance(Vec2::new(3.0, 5.0), a, b, 1.0), -7.0);
+
+    let crossing = segment_intersection(Vec2::new(0.0, 10.0), Vec2::new(20.0, 10.0), a, b);
+    assert_eq!(crossing, Some((0.5, 0.5)));
+  }
+}
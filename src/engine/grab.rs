@@ -0,0 +1,107 @@
+use macroquad::prelude::*;
+
+use super::*;
+
+pub struct Grab {
+  pub strength: f32,
+  pub damping: f32,
+  anchor: Option<Anchor>,
+  target: Vec2,
+}
+
+impl Grab {
+  pub fn new(strength: f32, damping: f32) -> Self {
+    return Self {
+      strength,
+      damping,
+      anchor: None,
+      target: Vec2::ZERO,
+    };
+  }
+
+  pub fn is_holding(&self) -> bool {
+    return self.anchor.is_some();
+  }
+
+  // picks the mesh triangle under the cursor, or failing that the closest point in `radius`
+  pub fn start(&mut self, world: &World, position: Vec2, radius: f32) -> bool {
+    self.target = position;
+    self.anchor = None;
+
+    for body in world.query_aabb(position, position) {
+      let s = match body {
+        Body::Shape(s) => s,
+        Body::Point(_) => continue,
+      };
+
+      let shape = &world.shapes[s];
+      for [a, b, c] in shape.triangles() {
+        let weights = barycentric(
+          position,
+          shape.points[a].position,
+          shape.points[b].position,
+          shape.points[c].position,
+        );
+
+        if let Some((wa, wb, wc)) = weights {
+          self.anchor = Some(Anchor::Shape(s, vec![(a, wa), (b, wb), (c, wc)]));
+          return true;
+        }
+      }
+    }
+
+    self.anchor = world.nearest_point(position, radius);
+
+    return self.is_holding();
+  }
+
+  pub fn drag(&mut self, position: Vec2) {
+    self.target = position;
+  }
+
+  pub fn release(&mut self) {
+    self.anchor = None;
+  }
+
+  pub fn apply(&self, world: &mut World, delta_time: f32) {
+    if let Some(anchor) = &self.anchor {
+      let position = anchor.position(&world.shapes, &world.points);
+      let velocity = anchor.velocity(&world.shapes, &world.points);
+      let inverse_mass = anchor.inverse_mass(&world.shapes, &world.points);
+
+      let force = (self.target - position) * self.strength - velocity * self.damping;
+      anchor.apply(
+        &mut world.shapes,
+        &mut world.points,
+        Vec2::ZERO,
+        force * inverse_mass * delta_time,
+      );
+    }
+  }
+
+  pub fn draw(&self, world: &World) {
+    if let Some(anchor) = &self.anchor {
+      let position = anchor.position(&world.shapes, &world.points);
+
+      draw_line_vec(position, self.target, 1.0, WHITE);
+      draw_circle_vec(position, 3.0, WHITE);
+    }
+  }
+}
+
+fn barycentric(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> Option<(f32, f32, f32)> {
+  let area = cross_2d(b - a, c - a);
+  if area == 0.0 {
+    return None;
+  }
+
+  let wa = cross_2d(b - p, c - p) / area;
+  let wb = cross_2d(c - p, a - p) / area;
+  let wc = 1.0 - wa - wb;
+
+  if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+    return None;
+  }
+
+  return Some((wa, wb, wc));
+}
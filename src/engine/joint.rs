@@ -0,0 +1,310 @@
+use std::f32::consts::{PI, TAU};
+
+use macroquad::prelude::*;
+
+use super::{PointMass, Shape};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Body {
+  Shape(usize),
+  Point(usize),
+}
+
+#[derive(Clone)]
+pub enum Anchor {
+  Point(usize),
+  // barycentric weights over points of a shape
+  Shape(usize, Vec<(usize, f32)>),
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+pub enum JointKind {
+  Pin,
+  Distance {
+    min: f32,
+    max: f32,
+  },
+  Weld,
+  Hinge {
+    lower: f32,
+    upper: f32,
+    motor_speed: f32,
+    max_motor_torque: f32,
+  },
+}
+
+pub struct Joint {
+  pub a: Anchor,
+  pub b: Anchor,
+  pub kind: JointKind,
+  reference_angle: f32,
+}
+
+#[allow(dead_code)]
+impl Anchor {
+  pub fn vertex(shape: usize, index: usize) -> Self {
+    return Anchor::Shape(shape, vec![(index, 1.0)]);
+  }
+
+  pub fn edge(shape: usize, a: usize, b: usize, t: f32) -> Self {
+    return Anchor::Shape(shape, vec![(a, 1.0 - t), (b, t)]);
+  }
+
+  pub fn body(&self) -> Body {
+    return match self {
+      Anchor::Point(i) => Body::Point(*i),
+      Anchor::Shape(s, _) => Body::Shape(*s),
+    };
+  }
+
+  pub fn position(&self, shapes: &Vec<Shape>, points: &Vec<PointMass>) -> Vec2 {
+    return match self {
+      Anchor::Point(i) => points[*i].position,
+      Anchor::Shape(s, weights) => weights.iter().fold(Vec2::ZERO, |total, (i, w)| {
+        total + shapes[*s].points[*i].position * *w
+      }),
+    };
+  }
+
+  pub fn velocity(&self, shapes: &Vec<Shape>, points: &Vec<PointMass>) -> Vec2 {
+    return match self {
+      Anchor::Point(i) => points[*i].velocity,
+      Anchor::Shape(s, weights) => weights.iter().fold(Vec2::ZERO, |total, (i, w)| {
+        total + shapes[*s].points[*i].velocity * *w
+      }),
+    };
+  }
+
+  // how easily the anchor moves, sum of w^2 / m over its points
+  pub fn inverse_mass(&self, shapes: &Vec<Shape>, points: &Vec<PointMass>) -> f32 {
+    return match self {
+      Anchor::Point(i) => inverse_mass(&points[*i], 1.0),
+      Anchor::Shape(s, weights) => weights
+        .iter()
+        .map(|(i, w)| inverse_mass(&shapes[*s].points[*i], *w))
+        .sum(),
+    };
+  }
+
+  // moves the anchor by `position` and changes its velocity by `velocity`, spread over its
+  // points so lighter and more heavily weighted points move more
+  pub fn apply(
+    &self,
+    shapes: &mut Vec<Shape>,
+    points: &mut Vec<PointMass>,
+    position: Vec2,
+    velocity: Vec2,
+  ) {
+    let total = self.inverse_mass(shapes, points);
+    if total == 0.0 {
+      return;
+    }
+
+    match self {
+      Anchor::Point(i) => {
+        points[*i].position += position;
+        points[*i].velocity += velocity;
+      }
+      Anchor::Shape(s, weights) => {
+        let shape = &mut shapes[*s];
+        // a sleeping shape skips its update, so either leave it alone or wake it before moving
+        // its points
+        if shape.is_sleeping() {
+          if position.length() <= 0.1 && velocity.length() <= 1.0 {
+            return;
+          }
+          shape.wake();
+        }
+
+        for (i, w) in weights.iter() {
+          let point = &mut shape.points[*i];
+          if point.locked {
+            continue;
+          }
+
+          let k = w / point.mass / total;
+
+          point.position += position * k;
+          point.velocity += velocity * k;
+        }
+      }
+    }
+  }
+}
+
+impl Joint {
+  pub fn new(a: Anchor, b: Anchor, kind: JointKind, shapes: &Vec<Shape>) -> Self {
+    let reference_angle = body_rotation(b.body(), shapes) - body_rotation(a.body(), shapes);
+
+    return Self {
+      a,
+      b,
+      kind,
+      reference_angle,
+    };
+  }
+
+  pub fn solve(&self, shapes: &mut Vec<Shape>, points: &mut Vec<PointMass>, delta_time: f32) {
+    match self.kind {
+      JointKind::Pin => self.solve_distance(shapes, points, None),
+      JointKind::Distance { min, max } => self.solve_distance(shapes, points, Some((min, max))),
+      JointKind::Weld => {
+        self.solve_distance(shapes, points, None);
+        self.solve_angle(shapes, 0.0, 0.0);
+      }
+      JointKind::Hinge {
+        lower,
+        upper,
+        motor_speed,
+        max_motor_torque,
+      } => {
+        self.solve_distance(shapes, points, None);
+        self.solve_motor(shapes, motor_speed, max_motor_torque * delta_time);
+        self.solve_angle(shapes, lower, upper);
+      }
+    }
+  }
+
+  // without limits the anchors are pulled together, like a pin
+  fn solve_distance(
+    &self,
+    shapes: &mut Vec<Shape>,
+    points: &mut Vec<PointMass>,
+    limits: Option<(f32, f32)>,
+  ) {
+    let weight_a = self.a.inverse_mass(shapes, points);
+    let weight_b = self.b.inverse_mass(shapes, points);
+    let total = weight_a + weight_b;
+    if total == 0.0 {
+      return;
+    }
+
+    let diff = self.b.position(shapes, points) - self.a.position(shapes, points);
+    let relative_velocity = self.b.velocity(shapes, points) - self.a.velocity(shapes, points);
+    let dist = diff.length();
+
+    let (error, velocity) = if let Some((min, max)) = limits {
+      let n = diff.normalize_or_zero();
+      let stretch = if dist > max {
+        dist - max
+      } else if dist < min {
+        dist - min
+      } else {
+        return;
+      };
+
+      let speed = relative_velocity.dot(n);
+      let speed = if speed * stretch > 0.0 { speed } else { 0.0 };
+      (n * stretch, n * speed)
+    } else {
+      (diff, relative_velocity)
+    };
+
+    self.a.apply(
+      shapes,
+      points,
+      error * weight_a / total,
+      velocity * weight_a / total,
+    );
+    self.b.apply(
+      shapes,
+      points,
+      -error * weight_b / total,
+      -velocity * weight_b / total,
+    );
+  }
+
+  fn solve_angle(&self, shapes: &mut Vec<Shape>, lower: f32, upper: f32) {
+    let (weight_a, weight_b) = self.inverse_inertias(shapes);
+    let total = weight_a + weight_b;
+    if total == 0.0 {
+      return;
+    }
+
+    let angle = self.relative_angle(shapes);
+    let error = if angle < lower {
+      angle - lower
+    } else if angle > upper {
+      angle - upper
+    } else {
+      return;
+    };
+
+    let spin =
+      body_angular_velocity(self.b.body(), shapes) - body_angular_velocity(self.a.body(), shapes);
+    let spin = if spin * error > 0.0 { spin } else { 0.0 };
+
+    if let (Body::Shape(s), true) = (self.a.body(), weight_a > 0.0) {
+      let inertia = shapes[s].moment_of_inertia();
+      shapes[s].rotate(error * weight_a / total);
+      shapes[s].apply_torque(spin * weight_a / total * inertia);
+    }
+    if let (Body::Shape(s), true) = (self.b.body(), weight_b > 0.0) {
+      let inertia = shapes[s].moment_of_inertia();
+      shapes[s].rotate(-error * weight_b / total);
+      shapes[s].apply_torque(-spin * weight_b / total * inertia);
+    }
+  }
+
+  fn solve_motor(&self, shapes: &mut Vec<Shape>, speed: f32, max_impulse: f32) {
+    let (weight_a, weight_b) = self.inverse_inertias(shapes);
+    let total = weight_a + weight_b;
+    if total == 0.0 || max_impulse == 0.0 {
+      return;
+    }
+
+    let spin =
+      body_angular_velocity(self.b.body(), shapes) - body_angular_velocity(self.a.body(), shapes);
+    let impulse = ((speed - spin) / total).clamp(-max_impulse, max_impulse);
+
+    if let (Body::Shape(s), true) = (self.a.body(), weight_a > 0.0) {
+      shapes[s].apply_torque(-impulse);
+    }
+    if let (Body::Shape(s), true) = (self.b.body(), weight_b > 0.0) {
+      shapes[s].apply_torque(impulse);
+    }
+  }
+
+  fn relative_angle(&self, shapes: &Vec<Shape>) -> f32 {
+    let angle = body_rotation(self.b.body(), shapes)
+      - body_rotation(self.a.body(), shapes)
+      - self.reference_angle;
+
+    return (angle + PI).rem_euclid(TAU) - PI;
+  }
+
+  fn inverse_inertias(&self, shapes: &Vec<Shape>) -> (f32, f32) {
+    let inverse_inertia = |body: Body| match body {
+      Body::Shape(s) if !shapes[s].is_kinematic() => 1.0 / shapes[s].moment_of_inertia(),
+      _ => 0.0,
+    };
+
+    return (
+      inverse_inertia(self.a.body()),
+      inverse_inertia(self.b.body()),
+    );
+  }
+}
+
+fn inverse_mass(point: &PointMass, weight: f32) -> f32 {
+  if point.locked || point.mass == 0.0 {
+    return 0.0;
+  }
+
+  return weight * weight / point.mass;
+}
+
+fn body_rotation(body: Body, shapes: &Vec<Shape>) -> f32 {
+  return match body {
+    Body::Shape(s) => shapes[s].rotation(),
+    Body::Point(_) => 0.0,
+  };
+}
+
+fn body_angular_velocity(body: Body, shapes: &Vec<Shape>) -> f32 {
+  return match body {
+    Body::Shape(s) => shapes[s].angular_velocity(),
+    Body::Point(_) => 0.0,
+  };
+}
@@ -0,0 +1,187 @@
+use macroquad::prelude::*;
+
+use super::{cross_2d, invert_matrix, PointMass};
+
+#[derive(Clone, Copy)]
+pub enum MatchingMode {
+  Rigid,
+  Linear(f32),
+  Quadratic(f32),
+}
+
+pub struct MatchFrame {
+  pub indices: Vec<usize>,
+  pub rest: Vec<Vec2>,
+  pub position: Vec2,
+  pub rotation: f32,
+  scale: f32,
+  linear_inverse: Option<Mat2>,
+  quadratic_inverse: Option<[[f32; 5]; 5]>,
+}
+
+impl MatchFrame {
+  pub fn new(indices: Vec<usize>, rest: &Vec<Vec2>, points: &Vec<PointMass>) -> Self {
+    let mut frame = Self {
+      rest: indices.iter().map(|i| rest[*i]).collect(),
+      indices,
+      position: Vec2::ZERO,
+      rotation: 0.0,
+      scale: 1.0,
+      linear_inverse: None,
+      quadratic_inverse: None,
+    };
+
+    frame.recenter(points);
+
+    return frame;
+  }
+
+  // shifts the rest offsets onto the current center of mass and rebuilds the matrices that
+  // depend on the masses, returning how far the rest center moved
+  pub fn recenter(&mut self, points: &Vec<PointMass>) -> Vec2 {
+    let mut total_position = Vec2::ZERO;
+    let mut total_mass = 0.0;
+    for (k, i) in self.indices.iter().enumerate() {
+      total_position += self.rest[k] * points[*i].mass;
+      total_mass += points[*i].mass;
+    }
+
+    let offset = total_position / total_mass;
+    self.scale = 0.0;
+    for q in self.rest.iter_mut() {
+      *q -= offset;
+      self.scale = self.scale.max(q.length());
+    }
+    if self.scale == 0.0 {
+      self.scale = 1.0;
+    }
+
+    let mut aqq = Mat2::ZERO;
+    let mut aqq_quadratic = [[0.0; 5]; 5];
+    for (k, i) in self.indices.iter().enumerate() {
+      let m = points[*i].mass;
+      aqq += outer(self.rest[k], self.rest[k]) * m;
+
+      let q = quadratic_terms(self.rest[k] / self.scale);
+      for r in 0..5 {
+        for c in 0..5 {
+          aqq_quadratic[r][c] += m * q[r] * q[c];
+        }
+      }
+    }
+
+    self.linear_inverse = if aqq.determinant().abs() > f32::EPSILON {
+      Some(aqq.inverse())
+    } else {
+      None
+    };
+    self.quadratic_inverse = invert_matrix(aqq_quadratic);
+
+    let (angle_s, angle_c) = self.rotation.sin_cos();
+    self.position += Vec2::new(
+      angle_c * offset.x - angle_s * offset.y,
+      angle_s * offset.x + angle_c * offset.y,
+    );
+
+    return offset;
+  }
+
+  pub fn fit(&mut self, points: &Vec<PointMass>) {
+    let mut total_position = Vec2::ZERO;
+    let mut total_mass = 0.0;
+    for i in self.indices.iter() {
+      total_position += points[*i].position * points[*i].mass;
+      total_mass += points[*i].mass;
+    }
+
+    self.position = total_position / total_mass;
+
+    let mut a = 0.0;
+    let mut b = 0.0;
+    for (k, i) in self.indices.iter().enumerate() {
+      let v = &points[*i];
+      let r = v.position - self.position;
+      a += v.mass * r.dot(self.rest[k]);
+      b += v.mass * cross_2d(r, self.rest[k]);
+    }
+
+    self.rotation = -(b.atan2(a));
+  }
+
+  pub fn goals(&self, points: &Vec<PointMass>, mode: MatchingMode) -> Vec<Vec2> {
+    let rotation = Mat2::from_angle(self.rotation);
+
+    match mode {
+      MatchingMode::Linear(beta) => {
+        if let Some(linear_inverse) = self.linear_inverse {
+          let mut apq = Mat2::ZERO;
+          for (k, i) in self.indices.iter().enumerate() {
+            let p = points[*i].position - self.position;
+            apq += outer(p, self.rest[k]) * points[*i].mass;
+          }
+
+          let mut a = apq * linear_inverse;
+          let det = a.determinant();
+          a = if det > f32::EPSILON {
+            a * (1.0 / det.sqrt())
+          } else {
+            rotation
+          };
+
+          let m = a * beta + rotation * (1.0 - beta);
+          return self.rest.iter().map(|q| m * *q + self.position).collect();
+        }
+      }
+      MatchingMode::Quadratic(beta) => {
+        if let Some(quadratic_inverse) = self.quadratic_inverse {
+          let mut apq = [Vec2::ZERO; 5];
+          for (k, i) in self.indices.iter().enumerate() {
+            let p = (points[*i].position - self.position) * points[*i].mass;
+            let q = quadratic_terms(self.rest[k] / self.scale);
+            for c in 0..5 {
+              apq[c] += p * q[c];
+            }
+          }
+
+          let mut m = [Vec2::ZERO; 5];
+          for c in 0..5 {
+            for r in 0..5 {
+              m[c] += apq[r] * quadratic_inverse[r][c];
+            }
+            m[c] *= beta;
+          }
+          m[0] += rotation.x_axis * self.scale * (1.0 - beta);
+          m[1] += rotation.y_axis * self.scale * (1.0 - beta);
+
+          return self
+            .rest
+            .iter()
+            .map(|q| {
+              let q = quadratic_terms(*q / self.scale);
+              let mut goal = self.position;
+              for c in 0..5 {
+                goal += m[c] * q[c];
+              }
+              goal
+            })
+            .collect();
+        }
+      }
+      MatchingMode::Rigid => {}
+    }
+
+    return self
+      .rest
+      .iter()
+      .map(|q| rotation * *q + self.position)
+      .collect();
+  }
+}
+
+fn outer(a: Vec2, b: Vec2) -> Mat2 {
+  return Mat2::from_cols(a * b.x, a * b.y);
+}
+
+fn quadratic_terms(q: Vec2) -> [f32; 5] {
+  return [q.x, q.y, q.x * q.x, q.y * q.y, q.x * q.y];
+}
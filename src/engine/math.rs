@@ -17,10 +17,108 @@ pub fn inverse_lerp_f32(v: f32, a: f32, b: f32) -> f32 {
 
 #[allow(dead_code)]
 pub fn inverse_lerp_vec(v: Vec2, a: Vec2, b: Vec2) -> f32 {
-  return (v.x - a.x) / (b.x - a.x);
+  let ab = b - a;
+  let length_squared = ab.length_squared();
+  if length_squared == 0.0 {
+    return 0.0;
+  }
+
+  return (v - a).dot(ab) / length_squared;
 }
 
 #[allow(dead_code)]
 pub fn cross_2d(a: Vec2, b: Vec2) -> f32 {
   return a.x * b.y - a.y * b.x;
 }
+
+#[allow(dead_code)]
+pub fn polygon_area(points: &Vec<Vec2>) -> f32 {
+  let mut area = 0.0;
+  let mut prev = points.len() - 1;
+
+  for i in 0..points.len() {
+    area += cross_2d(points[prev], points[i]);
+    prev = i;
+  }
+
+  return area * 0.5;
+}
+
+#[allow(dead_code)]
+pub fn polygon_centroid(points: &Vec<Vec2>) -> Vec2 {
+  let mut centroid = Vec2::ZERO;
+  let mut prev = points.len() - 1;
+
+  for i in 0..points.len() {
+    centroid += (points[prev] + points[i]) * cross_2d(points[prev], points[i]);
+    prev = i;
+  }
+
+  return centroid / (6.0 * polygon_area(points));
+}
+
+#[allow(dead_code)]
+pub fn invert_matrix<const N: usize>(matrix: [[f32; N]; N]) -> Option<[[f32; N]; N]> {
+  let mut m = matrix;
+  let mut inverse = [[0.0; N]; N];
+  for i in 0..N {
+    inverse[i][i] = 1.0;
+  }
+
+  for col in 0..N {
+    let mut pivot = col;
+    for row in col + 1..N {
+      if m[row][col].abs() > m[pivot][col].abs() {
+        pivot = row;
+      }
+    }
+
+    if m[pivot][col].abs() < 1e-9 {
+      return None;
+    }
+
+    m.swap(col, pivot);
+    inverse.swap(col, pivot);
+
+    let d = m[col][col];
+    for c in 0..N {
+      m[col][c] /= d;
+      inverse[col][c] /= d;
+    }
+
+    for row in 0..N {
+      if row == col {
+        continue;
+      }
+
+      let f = m[row][col];
+      for c in 0..N {
+        m[row][c] -= f * m[col][c];
+        inverse[row][c] -= f * inverse[col][c];
+      }
+    }
+  }
+
+  return Some(inverse);
+}
+
+#[allow(dead_code)]
+pub fn value_noise(x: f32, y: f32) -> f32 {
+  let (x0, y0) = (x.floor(), y.floor());
+  let (tx, ty) = (x - x0, y - y0);
+  let (sx, sy) = (tx * tx * (3.0 - 2.0 * tx), ty * ty * (3.0 - 2.0 * ty));
+  let (i, j) = (x0 as i32, y0 as i32);
+
+  let top = lerp_f32(sx, hash_2d(i, j), hash_2d(i + 1, j));
+  let bottom = lerp_f32(sx, hash_2d(i, j + 1), hash_2d(i + 1, j + 1));
+
+  return lerp_f32(sy, top, bottom);
+}
+
+fn hash_2d(x: i32, y: i32) -> f32 {
+  let mut h = (x as u32).wrapping_mul(374761393) ^ (y as u32).wrapping_mul(668265263);
+  h = (h ^ (h >> 13)).wrapping_mul(1274126177);
+  h ^= h >> 16;
+
+  return (h as f32 / u32::MAX as f32) * 2.0 - 1.0;
+}
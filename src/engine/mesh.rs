@@ -4,7 +4,7 @@ use macroquad::prelude::*;
 
 use crate::config::*;
 
-use super::{cross_2d, PointMass};
+use super::{cross_2d, point_in_triangle, polygon_winding, PointMass};
 
 pub struct SoftMesh {
   tex_coords: Vec<(f32, f32)>,
@@ -45,6 +45,14 @@ impl SoftMesh {
     self.material.set_texture("tex", texture);
   }
 
+  pub fn triangles(&self) -> Vec<[usize; 3]> {
+    return self
+      .indices
+      .chunks(3)
+      .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+      .collect();
+  }
+
   pub fn update_triangles(&mut self, points: &Vec<Vec2>) {
     if let Some(triangles) = triangulate(points.clone()) {
       self.indices = triangles.iter().map(|i| *i as u16).collect();
@@ -84,7 +92,38 @@ impl SoftMesh {
   }
 }
 
+pub fn triangulation(shape: &Vec<Vec2>) -> Vec<[usize; 3]> {
+  return match triangulate(shape.clone()) {
+    Some(triangles) => triangles.chunks(3).map(|t| [t[0], t[1], t[2]]).collect(),
+    None => Vec::new(),
+  };
+}
+
+pub fn triangulation_edges(shape: &Vec<Vec2>) -> Vec<(usize, usize)> {
+  let mut edges = Vec::new();
+
+  if let Some(triangles) = triangulate(shape.clone()) {
+    for triangle in triangles.chunks(3) {
+      for k in 0..3 {
+        let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+        let edge = (a.min(b), a.max(b));
+
+        if !edges.contains(&edge) {
+          edges.push(edge);
+        }
+      }
+    }
+  }
+
+  return edges;
+}
+
 fn triangulate(shape: Vec<Vec2>) -> Option<Vec<usize>> {
+  let winding = polygon_winding(&shape);
+  if winding == 0.0 {
+    return None;
+  }
+
   let mut indices = (0..shape.len()).collect::<Vec<usize>>();
   let mut triangles = Vec::<usize>::new();
 
@@ -99,7 +138,7 @@ fn triangulate(shape: Vec<Vec2>) -> Option<Vec<usize>> {
       let vp = shape[vert] - shape[prev];
       let vn = shape[vert] - shape[next];
 
-      if cross_2d(vp, vn) > 0.0 {
+      if cross_2d(vp, vn) * winding > 0.0 {
         continue; // vertex is convex
       }
 
@@ -153,28 +192,6 @@ fn get_adjacent(i: usize, list: &Vec<usize>) -> (usize, usize) {
   return (list[i - 1], list[i + 1]);
 }
 
-fn point_in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
-  let ab = b - a;
-  let bc = c - b;
-  let ca = a - c;
-
-  let ap = p - a;
-  let bp = p - b;
-  let cp = p - c;
-
-  if cross_2d(ab, ap) < 0.0 {
-    return false;
-  }
-  if cross_2d(bc, bp) < 0.0 {
-    return false;
-  }
-  if cross_2d(ca, cp) < 0.0 {
-    return false;
-  }
-
-  return true;
-}
-
 fn generate_uv(shape: &Vec<Vec2>) -> Vec<(f32, f32)> {
   let mut min = Vec2::new(INFINITY, INFINITY);
   let mut max = Vec2::new(NEG_INFINITY, NEG_INFINITY);
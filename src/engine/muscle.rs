@@ -0,0 +1,92 @@
+use std::f32::consts::TAU;
+
+use super::{inverse_lerp_f32, lerp_f32, Spring};
+
+#[allow(dead_code)]
+pub enum Actuation {
+  Sine {
+    amplitude: f32,
+    frequency: f32,
+    phase: f32,
+  },
+  // (time, value) pairs, looped over the time of the last key
+  Keyframes(Vec<(f32, f32)>),
+  Signal,
+}
+
+pub struct Muscle {
+  pub spring: Spring,
+  pub actuation: Actuation,
+  pub stiffness: f32,
+  pub signal: f32,
+  length: f32,
+  strength: f32,
+}
+
+impl Muscle {
+  pub fn new(spring: Spring, actuation: Actuation) -> Self {
+    return Self {
+      length: spring.length(),
+      strength: spring.strength(),
+      spring,
+      actuation,
+      stiffness: 0.0,
+      signal: 0.0,
+    };
+  }
+
+  pub fn activation(&self, time: f32) -> f32 {
+    return match &self.actuation {
+      Actuation::Sine {
+        amplitude,
+        frequency,
+        phase,
+      } => amplitude * (TAU * frequency * time + phase).sin(),
+      Actuation::Keyframes(keys) => sample_keyframes(keys, time),
+      Actuation::Signal => self.signal,
+    };
+  }
+
+  // the rest length stretches by the activation, and the strength follows it by `stiffness`
+  pub fn update(&mut self, time: f32) {
+    let activation = self.activation(time);
+
+    self
+      .spring
+      .set_length(self.length * (1.0 + activation).max(0.0));
+    self
+      .spring
+      .set_strength(self.strength * (1.0 + self.stiffness * activation).max(0.0));
+  }
+}
+
+fn sample_keyframes(keys: &Vec<(f32, f32)>, time: f32) -> f32 {
+  if keys.is_empty() {
+    return 0.0;
+  }
+
+  let period = keys[keys.len() - 1].0;
+  let time = if period > 0.0 {
+    time.rem_euclid(period)
+  } else {
+    0.0
+  };
+
+  if time <= keys[0].0 {
+    return keys[0].1;
+  }
+
+  for i in 1..keys.len() {
+    let (t0, v0) = keys[i - 1];
+    let (t1, v1) = keys[i];
+
+    if time <= t1 {
+      if t1 == t0 {
+        return v1;
+      }
+      return lerp_f32(inverse_lerp_f32(time, t0, t1), v0, v1);
+    }
+  }
+
+  return keys[keys.len() - 1].1;
+}
@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+use macroquad::models;
+use macroquad::prelude::*;
+
+use crate::config::*;
+
+use super::*;
+
+// the default batch holds 5000 indices, 6 per particle quad
+const DRAW_BATCH: usize = 800;
+
+// particles are stored as parallel arrays so thousands of them stay cheap to step
+pub struct Particles {
+  positions: Vec<Vec2>,
+  previous_positions: Vec<Vec2>,
+  velocities: Vec<Vec2>,
+  masses: Vec<f32>,
+  diameters: Vec<f32>,
+  colors: Vec<Color>,
+  ages: Vec<f32>,
+  lifetimes: Vec<f32>,
+  pub gravity_scale: f32,
+  pub drag: Drag,
+  pub restitution: f32,
+  pub self_collision: bool,
+  pub category: u32,
+  pub mask: u32,
+  grid: SpatialHash,
+}
+
+pub struct Emitter {
+  pub position: Vec2,
+  pub direction: Vec2,
+  pub spread: f32,
+  pub speed: (f32, f32),
+  // particles per second
+  pub rate: f32,
+  pub lifetime: (f32, f32),
+  pub diameter: (f32, f32),
+  pub mass: f32,
+  pub color: Color,
+  // spawn along a line of this width across `direction`, for things like rain
+  pub width: f32,
+  accumulator: f32,
+}
+
+struct SpatialHash {
+  cell_size: f32,
+  cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl Particles {
+  pub fn new() -> Self {
+    return Self {
+      positions: Vec::new(),
+      previous_positions: Vec::new(),
+      velocities: Vec::new(),
+      masses: Vec::new(),
+      diameters: Vec::new(),
+      colors: Vec::new(),
+      ages: Vec::new(),
+      lifetimes: Vec::new(),
+      gravity_scale: 1.0,
+      drag: Drag::Quadratic(DRAG_COEFFICIENT),
+      restitution: 0.3,
+      self_collision: false,
+      category: 1,
+      mask: u32::MAX,
+      grid: SpatialHash::new(8.0),
+    };
+  }
+
+  pub fn len(&self) -> usize {
+    return self.positions.len();
+  }
+
+  pub fn spawn(
+    &mut self,
+    position: Vec2,
+    velocity: Vec2,
+    mass: f32,
+    diameter: f32,
+    lifetime: f32,
+    color: Color,
+  ) {
+    self.positions.push(position);
+    self.previous_positions.push(position);
+    self.velocities.push(velocity);
+    self.masses.push(mass);
+    self.diameters.push(diameter);
+    self.colors.push(color);
+    self.ages.push(0.0);
+    self.lifetimes.push(lifetime);
+  }
+
+  #[allow(dead_code)]
+  pub fn clear(&mut self) {
+    while self.len() > 0 {
+      self.remove(self.len() - 1);
+    }
+  }
+
+  fn remove(&mut self, i: usize) {
+    self.positions.swap_remove(i);
+    self.previous_positions.swap_remove(i);
+    self.velocities.swap_remove(i);
+    self.masses.swap_remove(i);
+    self.diameters.swap_remove(i);
+    self.colors.swap_remove(i);
+    self.ages.swap_remove(i);
+    self.lifetimes.swap_remove(i);
+  }
+
+  pub fn update(
+    &mut self,
+    shapes: &mut Vec<Shape>,
+    colliders: &Vec<Collider>,
+    gravity: Vec2,
+    delta_time: f32,
+  ) {
+    let mut i = 0;
+    while i < self.len() {
+      self.ages[i] += delta_time;
+      if self.ages[i] >= self.lifetimes[i] {
+        self.remove(i);
+      } else {
+        i += 1;
+      }
+    }
+
+    let gravity = gravity * self.gravity_scale * delta_time;
+    for i in 0..self.len() {
+      let velocity = self.velocities[i];
+      let speed = velocity.length();
+
+      let drag = match self.drag {
+        _ if speed == 0.0 => Vec2::ZERO,
+        Drag::Quadratic(coefficient) => {
+          coefficient * self.diameters[i] * speed * speed * -velocity / speed
+        }
+        Drag::Linear(coefficient) => coefficient * self.diameters[i] * -velocity,
+      };
+
+      self.velocities[i] += gravity + drag / self.masses[i] * delta_time;
+      self.previous_positions[i] = self.positions[i];
+      self.positions[i] += self.velocities[i] * delta_time;
+    }
+
+    let largest = self.diameters.iter().fold(0.0_f32, |m, d| m.max(*d));
+    self.grid.cell_size = (largest * 2.0).max(4.0);
+    self.grid.build(&self.positions);
+
+    if self.self_collision {
+      self.collide_particles();
+    }
+
+    let margin = Vec2::splat(largest * 0.5);
+    for shape in shapes.iter_mut() {
+      if shape.is_sensor() || shape.category() & self.mask == 0 || self.category & shape.mask() == 0
+      {
+        continue;
+      }
+
+      let (min, max) = shape.bounding_box;
+      for i in self.grid.query(min - margin, max + margin) {
+        let mut point = self.point(i);
+        if let Some(collision) = circle_shape_collision(&point, shape) {
+          resolve_particle_line(&mut point, shape, collision);
+          self.store(i, &point);
+        }
+      }
+    }
+
+    for collider in colliders.iter() {
+      let (min, max) = collider.bounds();
+      for i in self.grid.query(min - margin, max + margin) {
+        let mut point = self.point(i);
+        if let Some((normal, depth)) = collider.contact(&point, point.radius()) {
+          resolve_point_static(&mut point, normal, depth);
+          self.store(i, &point);
+        }
+      }
+    }
+  }
+
+  fn collide_particles(&mut self) {
+    for a in 0..self.len() {
+      let radius_a = self.diameters[a] * 0.5;
+      let offset = Vec2::splat(radius_a + self.grid.cell_size * 0.5);
+
+      for b in self
+        .grid
+        .query(self.positions[a] - offset, self.positions[a] + offset)
+      {
+        if b <= a {
+          continue;
+        }
+
+        let diff = self.positions[b] - self.positions[a];
+        let distance = diff.length();
+        let radius = radius_a + self.diameters[b] * 0.5;
+        if distance >= radius || distance == 0.0 {
+          continue;
+        }
+
+        let weight_a = 1.0 / self.masses[a];
+        let weight_b = 1.0 / self.masses[b];
+        let total = weight_a + weight_b;
+
+        let normal = diff / distance;
+        let depth = radius - distance;
+        self.positions[a] -= normal * depth * weight_a / total;
+        self.positions[b] += normal * depth * weight_b / total;
+
+        let approach = (self.velocities[b] - self.velocities[a]).dot(normal);
+        if approach < 0.0 {
+          let impulse = -(1.0 + self.restitution) * approach / total;
+          self.velocities[a] -= normal * impulse * weight_a;
+          self.velocities[b] += normal * impulse * weight_b;
+        }
+      }
+    }
+  }
+
+  // a stand-in point so particles can go through the shared collision helpers
+  fn point(&self, i: usize) -> PointMass {
+    let mut point = PointMass::new(self.positions[i], self.masses[i], false);
+    point.previous_position = self.previous_positions[i];
+    point.velocity = self.velocities[i];
+    point.diameter = self.diameters[i];
+    point.restitution = self.restitution;
+    return point;
+  }
+
+  fn store(&mut self, i: usize, point: &PointMass) {
+    self.positions[i] = point.position;
+    self.velocities[i] = point.velocity;
+  }
+
+  pub fn draw(&self) {
+    for start in (0..self.len()).step_by(DRAW_BATCH) {
+      let end = (start + DRAW_BATCH).min(self.len());
+      let mut vertices = Vec::with_capacity((end - start) * 4);
+      let mut indices = Vec::with_capacity((end - start) * 6);
+
+      for i in start..end {
+        let p = self.positions[i];
+        let r = self.diameters[i] * 0.5;
+        let color = self.colors[i];
+        let base = vertices.len() as u16;
+
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+          vertices.push(models::Vertex {
+            position: vec3(p.x + x * r, p.y + y * r, 0.0),
+            uv: vec2((x + 1.0) * 0.5, (y + 1.0) * 0.5),
+            color,
+          });
+        }
+        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+      }
+
+      draw_mesh(&Mesh {
+        vertices,
+        indices,
+        texture: None,
+      });
+    }
+  }
+}
+
+impl Emitter {
+  pub fn new(position: Vec2, direction: Vec2, rate: f32, color: Color) -> Self {
+    return Self {
+      position,
+      direction,
+      spread: 0.0,
+      speed: (0.0, 0.0),
+      rate,
+      lifetime: (5.0, 5.0),
+      diameter: (4.0, 4.0),
+      mass: 0.1,
+      color,
+      width: 0.0,
+      accumulator: 0.0,
+    };
+  }
+
+  pub fn emit(&mut self, particles: &mut Particles, delta_time: f32) {
+    self.accumulator += self.rate * delta_time;
+
+    let direction = self.direction.normalize_or_zero();
+    let across = Vec2::new(-direction.y, direction.x);
+
+    while self.accumulator >= 1.0 {
+      self.accumulator -= 1.0;
+
+      let angle = rand::gen_range(-0.5, 0.5) * self.spread;
+      let velocity = Mat2::from_angle(angle) * direction * range(self.speed);
+      let position = self.position + across * rand::gen_range(-0.5, 0.5) * self.width;
+
+      particles.spawn(
+        position,
+        velocity,
+        self.mass,
+        range(self.diameter),
+        range(self.lifetime),
+        self.color,
+      );
+    }
+  }
+
+  // spawns `count` particles at once in every direction, like debris from an impact
+  #[allow(dead_code)]
+  pub fn burst(&self, particles: &mut Particles, count: usize) {
+    for _ in 0..count {
+      let velocity = Vec2::from_angle(rand::gen_range(0.0, TAU)) * range(self.speed);
+
+      particles.spawn(
+        self.position,
+        velocity,
+        self.mass,
+        range(self.diameter),
+        range(self.lifetime),
+        self.color,
+      );
+    }
+  }
+}
+
+impl SpatialHash {
+  fn new(cell_size: f32) -> Self {
+    return Self {
+      cell_size,
+      cells: HashMap::new(),
+    };
+  }
+
+  fn cell(&self, position: Vec2) -> (i32, i32) {
+    return (
+      (position.x / self.cell_size).floor() as i32,
+      (position.y / self.cell_size).floor() as i32,
+    );
+  }
+
+  fn build(&mut self, positions: &Vec<Vec2>) {
+    self.cells.retain(|_, cell| !cell.is_empty());
+    for cell in self.cells.values_mut() {
+      cell.clear();
+    }
+
+    for (i, position) in positions.iter().enumerate() {
+      let cell = self.cell(*position);
+      self.cells.entry(cell).or_default().push(i);
+    }
+  }
+
+  fn query(&self, min: Vec2, max: Vec2) -> Vec<usize> {
+    let (x0, y0) = self.cell(min);
+    let (x1, y1) = self.cell(max);
+
+    let mut found = Vec::new();
+    // a huge box covers more cells than there are filled ones
+    if ((x1 - x0 + 1) as i64) * ((y1 - y0 + 1) as i64) > self.cells.len() as i64 {
+      for (cell, items) in self.cells.iter() {
+        if cell.0 >= x0 && cell.0 <= x1 && cell.1 >= y0 && cell.1 <= y1 {
+          found.extend_from_slice(items);
+        }
+      }
+      return found;
+    }
+
+    for x in x0..=x1 {
+      for y in y0..=y1 {
+        if let Some(items) = self.cells.get(&(x, y)) {
+          found.extend_from_slice(items);
+        }
+      }
+    }
+
+    return found;
+  }
+}
+
+fn range((min, max): (f32, f32)) -> f32 {
+  if min >= max {
+    return min;
+  }
+
+  return rand::gen_range(min, max);
+}
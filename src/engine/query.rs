@@ -0,0 +1,139 @@
+use macroquad::prelude::*;
+
+use super::*;
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+  pub shape: usize,
+  pub edge: (usize, usize),
+  pub point: Vec2,
+  // outward normal of the edge that was hit
+  pub normal: Vec2,
+  pub distance: f32,
+}
+
+// every crossing of the segment from `start` to `end` with the shape's outline, entering and
+// leaving, as (edge, fraction along the segment, normal) sorted along the segment
+pub fn raycast_shape(shape: &Shape, start: Vec2, end: Vec2) -> Vec<((usize, usize), f32, Vec2)> {
+  let mut crossings = Vec::new();
+
+  let (min, max) = shape.bounding_box;
+  if !segment_hits_box(start, end, min, max) {
+    return crossings;
+  }
+
+  let winding = outline_winding(shape);
+  let np = shape.points.len();
+
+  let mut prev = np - 1;
+  for i in 0..np {
+    let a = shape.points[prev].position;
+    let b = shape.points[i].position;
+
+    if let Some((s, _)) = segment_intersection(start, end, a, b) {
+      crossings.push(((prev, i), s, outward_normal(a, b, winding)));
+    }
+
+    prev = i;
+  }
+
+  crossings.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+  return crossings;
+}
+
+// sweeps a circle from `start` to `end` and returns the first edge it touches, the normal
+// points from the contact towards the circle's center
+pub fn circle_cast_shape(
+  shape: &Shape,
+  start: Vec2,
+  end: Vec2,
+  radius: f32,
+) -> Option<((usize, usize), f32, Vec2)> {
+  let (min, max) = shape.bounding_box;
+  if !segment_hits_box(
+    start,
+    end,
+    min - Vec2::splat(radius),
+    max + Vec2::splat(radius),
+  ) {
+    return None;
+  }
+
+  let np = shape.points.len();
+  let mut closest: Option<((usize, usize), f32, Vec2)> = None;
+  let mut consider = |edge: (usize, usize), s: f32, normal: Vec2| {
+    if closest.map_or(true, |(_, best, _)| s < best) {
+      closest = Some((edge, s, normal));
+    }
+  };
+
+  let mut prev = np - 1;
+  for i in 0..np {
+    let a = shape.points[prev].position;
+    let b = shape.points[i].position;
+    let n = outward_normal(a, b, 1.0);
+
+    // the edge pushed out by the radius on both sides
+    for side in [n, -n] {
+      let offset = side * radius;
+      if let Some((s, _)) = segment_intersection(start, end, a + offset, b + offset) {
+        consider((prev, i), s, side);
+      }
+    }
+
+    // the rounded corner at b
+    if let Some(s) = segment_circle(start, end, b, radius) {
+      let center = lerp_vec(s, start, end);
+      consider((prev, i), s, (center - b).normalize_or_zero());
+    }
+
+    prev = i;
+  }
+
+  return closest;
+}
+
+// first fraction along the segment where it enters the circle
+fn segment_circle(start: Vec2, end: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+  let d = end - start;
+  let f = start - center;
+
+  let a = d.dot(d);
+  let b = 2.0 * f.dot(d);
+  let c = f.dot(f) - radius * radius;
+  let discriminant = b * b - 4.0 * a * c;
+  if a == 0.0 || discriminant < 0.0 {
+    return None;
+  }
+
+  let s = (-b - discriminant.sqrt()) / (2.0 * a);
+  if s < 0.0 || s > 1.0 {
+    return None;
+  }
+
+  return Some(s);
+}
+
+fn segment_hits_box(start: Vec2, end: Vec2, min: Vec2, max: Vec2) -> bool {
+  let d = end - start;
+  let mut enter: f32 = 0.0;
+  let mut exit: f32 = 1.0;
+
+  for (s, d, min, max) in [(start.x, d.x, min.x, max.x), (start.y, d.y, min.y, max.y)] {
+    if d == 0.0 {
+      if s < min || s > max {
+        return false;
+      }
+      continue;
+    }
+
+    let t0 = (min - s) / d;
+    let t1 = (max - s) / d;
+    enter = enter.max(t0.min(t1));
+    exit = exit.min(t0.max(t1));
+  }
+
+  return enter <= exit;
+}
@@ -0,0 +1,24 @@
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SensorEventKind {
+  Enter,
+  Exit,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
+pub struct SensorEvent {
+  pub sensor: usize,
+  pub body: Body,
+  pub kind: SensorEventKind,
+  // on exit this is the count from the last frame the body was overlapping
+  pub points_inside: usize,
+}
+
+pub fn points_inside(sensor: &Shape, points: &Vec<PointMass>) -> usize {
+  return points
+    .iter()
+    .filter(|p| point_shape_collision(p.position, sensor).is_some())
+    .count();
+}
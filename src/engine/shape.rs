@@ -1,4 +1,4 @@
-use std::f32::{INFINITY, NEG_INFINITY};
+use std::f32::{consts::PI, consts::TAU, INFINITY, NEG_INFINITY};
 
 use macroquad::prelude::*;
 
@@ -6,16 +6,39 @@ use crate::config::*;
 
 use super::*;
 
+struct Pin {
+  index: usize,
+  anchor: Vec2,
+  previous: Vec2,
+}
+
 pub struct Shape {
   pub bounding_box: (Vec2, Vec2),
   lock_frame: bool,
   pub position: Vec2,
-  rotation: f32,
-  frame: Vec<Vec2>,
+  frame_velocity: Vec2,
+  angular_velocity: f32,
+  target: Option<(Vec2, f32)>,
+  frame: MatchFrame,
+  clusters: Vec<MatchFrame>,
+  matching: MatchingMode,
+  can_sleep: bool,
+  sleeping: bool,
+  sleep_timer: f32,
+  submerged: f32,
+  category: u32,
+  mask: u32,
+  sensor: bool,
+  one_way: Option<(Vec2, f32)>,
   pub points: Vec<PointMass>,
   frame_points: Vec<PointMass>,
   np: usize,
   springs: Vec<Spring>,
+  hub_springs: Vec<Spring>,
+  angle_springs: Vec<AngleSpring>,
+  muscles: Vec<Muscle>,
+  pins: Vec<Pin>,
+  time: f32,
   frame_springs: Vec<Spring>,
   mesh: SoftMesh,
 }
@@ -31,12 +54,11 @@ impl Shape {
     let mut min = Vec2::new(INFINITY, INFINITY);
     let mut max = Vec2::new(NEG_INFINITY, NEG_INFINITY);
     let mut total_position = Vec2::ZERO;
+    let mut total_mass = 0.0;
 
     let mut points = Vec::new();
     let mut frame_points = Vec::new();
-    let mut frame = Vec::new();
 
-    let mut springs = Vec::new();
     let mut frame_springs = Vec::new();
 
     let np = input_points.len();
@@ -45,57 +67,504 @@ impl Shape {
       min = min.min(point.0);
       max = max.max(point.0);
 
-      total_position += point.0;
+      total_position += point.0 * point.1;
+      total_mass += point.1;
     });
 
-    let position = total_position / np as f32;
+    let position = total_position / total_mass;
 
     input_points.iter().for_each(|point| {
       let r = point.0 - position;
       points.push(PointMass::new(r * scale + position, point.1, false));
       frame_points.push(PointMass::new(r * scale, 0.0, false));
-      frame.push(r * scale);
     });
 
-    add_springs(&mut springs, 1, body_strength, &points);
-    add_springs(&mut springs, 2, body_strength, &points);
-
     for i in 0..np {
       frame_springs.push(Spring::new(frame_strength.0, 0.0, frame_strength.1, i, i));
     }
 
-    let mesh = SoftMesh::generate(frame.clone());
+    let positions = points.iter().map(|p| p.position).collect();
+    let frame = MatchFrame::new((0..np).collect(), &positions, &points);
+    let mesh = SoftMesh::generate(frame.rest.clone());
+
+    let springs = SpringGroup::new(
+      SpringTopology::Neighbors(vec![1, 2]),
+      body_strength.0,
+      body_strength.1,
+    )
+    .build(&frame.rest);
 
     return Self {
       bounding_box: (min, max),
       lock_frame,
       position,
-      rotation: 0.0,
+      frame_velocity: Vec2::ZERO,
+      angular_velocity: 0.0,
+      target: None,
       frame,
+      clusters: Vec::new(),
+      matching: MatchingMode::Rigid,
+      can_sleep: true,
+      sleeping: false,
+      sleep_timer: 0.0,
+      submerged: 0.0,
+      category: 1,
+      mask: u32::MAX,
+      sensor: false,
+      one_way: None,
       points,
       frame_points,
       np,
       springs,
+      hub_springs: Vec::new(),
+      angle_springs: Vec::new(),
+      muscles: Vec::new(),
+      pins: Vec::new(),
+      time: 0.0,
       frame_springs,
       mesh,
     };
   }
 
   pub fn apply_force(&mut self, force: Vec2) {
+    self.wake();
     for point in self.points.iter_mut() {
       point.apply_force(force);
     }
   }
 
+  pub fn apply_gravity(&mut self, gravity: Vec2, delta_time: f32) {
+    if self.sleeping || self.sensor {
+      return;
+    }
+
+    for point in self.points.iter_mut() {
+      point.apply_gravity(point.gravity(gravity) * delta_time);
+    }
+  }
+
   pub fn set_velocity(&mut self, velocity: Vec2) {
+    self.wake();
+    for point in self.points.iter_mut() {
+      if !point.locked {
+        point.velocity = velocity;
+      }
+    }
+  }
+
+  #[allow(dead_code)]
+  pub fn set_density(&mut self, density: f32) {
+    let mass = density * polygon_area(&self.frame.rest).abs() / self.np as f32;
+
+    for point in self.points.iter_mut() {
+      point.set_mass(mass);
+    }
+
+    self.frame.recenter(&self.points);
+    self.position = self.frame.position;
+
+    for cluster in self.clusters.iter_mut() {
+      cluster.recenter(&self.points);
+    }
+  }
+
+  pub fn set_matching_mode(&mut self, mode: MatchingMode) {
+    self.matching = mode;
+  }
+
+  pub fn set_clusters(&mut self, clusters: Vec<Vec<usize>>) {
+    self.clusters = clusters
+      .into_iter()
+      .filter(|indices| !indices.is_empty())
+      .map(|indices| {
+        let mut cluster = MatchFrame::new(indices, &self.frame.rest, &self.points);
+        cluster.fit(&self.points);
+        cluster
+      })
+      .collect();
+  }
+
+  pub fn generate_clusters(&mut self, radius: f32) {
+    let rest = &self.frame.rest;
+    let mut seeds: Vec<usize> = Vec::new();
+
+    for i in 0..self.np {
+      if seeds
+        .iter()
+        .all(|s| rest[*s].distance(rest[i]) > radius * 0.5)
+      {
+        seeds.push(i);
+      }
+    }
+
+    let clusters = seeds
+      .iter()
+      .map(|s| {
+        let mut by_distance = (0..self.np).collect::<Vec<usize>>();
+        by_distance.sort_by(|a, b| {
+          let da = rest[*s].distance(rest[*a]);
+          let db = rest[*s].distance(rest[*b]);
+          da.partial_cmp(&db).unwrap()
+        });
+
+        let count = by_distance
+          .iter()
+          .filter(|i| rest[*s].distance(rest[**i]) <= radius)
+          .count()
+          .max(3.min(self.np));
+
+        let mut indices = by_distance[0..count].to_vec();
+        indices.sort();
+        indices
+      })
+      .collect();
+
+    self.set_clusters(clusters);
+  }
+
+  #[allow(dead_code)]
+  pub fn set_can_sleep(&mut self, can_sleep: bool) {
+    self.can_sleep = can_sleep;
+    if !can_sleep {
+      self.wake();
+    }
+  }
+
+  pub fn is_sleeping(&self) -> bool {
+    return self.sleeping;
+  }
+
+  pub fn sleep(&mut self) {
+    self.sleeping = true;
+    for point in self.points.iter_mut() {
+      point.velocity = Vec2::ZERO;
+    }
+  }
+
+  // only resets the sleep timer of a sleeping shape, awake ones are left to the kinetic energy
+  // check
+  pub fn wake(&mut self) {
+    if !self.sleeping {
+      return;
+    }
+
+    self.sleeping = false;
+    self.sleep_timer = 0.0;
+  }
+
+  pub fn rotation(&self) -> f32 {
+    return self.frame.rotation;
+  }
+
+  // turns the points around the center of mass without touching their velocities
+  pub fn rotate(&mut self, angle: f32) {
+    let center = self.center_of_mass();
+    let rotation = Mat2::from_angle(angle);
+
+    for point in self.points.iter_mut() {
+      if !point.locked {
+        point.position = rotation * (point.position - center) + center;
+      }
+    }
+  }
+
+  pub fn mass(&self) -> f32 {
+    return self.points.iter().map(|p| p.mass).sum();
+  }
+
+  pub fn center_of_mass(&self) -> Vec2 {
+    let total = self
+      .points
+      .iter()
+      .fold(Vec2::ZERO, |total, p| total + p.position * p.mass);
+    return total / self.mass();
+  }
+
+  pub fn velocity(&self) -> Vec2 {
+    if self.lock_frame {
+      return self.frame_velocity;
+    }
+
+    let total = self
+      .points
+      .iter()
+      .fold(Vec2::ZERO, |total, p| total + p.velocity * p.mass);
+    return total / self.mass();
+  }
+
+  pub fn moment_of_inertia(&self) -> f32 {
+    let center = self.center_of_mass();
+    return self
+      .points
+      .iter()
+      .map(|p| p.mass * p.position.distance_squared(center))
+      .sum();
+  }
+
+  pub fn angular_velocity(&self) -> f32 {
+    if self.lock_frame {
+      return self.angular_velocity;
+    }
+
+    let center = self.center_of_mass();
+    let velocity = self.velocity();
+    let momentum: f32 = self
+      .points
+      .iter()
+      .map(|p| p.mass * cross_2d(p.position - center, p.velocity - velocity))
+      .sum();
+
+    return momentum / self.moment_of_inertia();
+  }
+
+  // splits an impulse applied at `position` into a push on the center of mass and a spin
+  // around it
+  pub fn apply_force_at(&mut self, force: Vec2, position: Vec2) {
+    let center = self.center_of_mass();
+    let mass = self.mass();
+
+    self.wake();
+    for point in self.points.iter_mut() {
+      point.apply_gravity(force / mass);
+    }
+
+    self.apply_torque(cross_2d(position - center, force));
+  }
+
+  pub fn apply_torque(&mut self, torque: f32) {
+    let center = self.center_of_mass();
+    let inertia = self.moment_of_inertia();
+    if inertia == 0.0 {
+      return;
+    }
+
+    self.wake();
+    for point in self.points.iter_mut() {
+      let r = point.position - center;
+      point.apply_gravity(Vec2::new(-r.y, r.x) * torque / inertia);
+    }
+  }
+
+  #[allow(dead_code)]
+  pub fn set_gravity_scale(&mut self, scale: f32) {
+    for point in self.points.iter_mut() {
+      point.gravity_scale = scale;
+    }
+  }
+
+  #[allow(dead_code)]
+  pub fn set_gravity_direction(&mut self, direction: Option<Vec2>) {
+    for point in self.points.iter_mut() {
+      point.gravity_direction = direction;
+    }
+  }
+
+  #[allow(dead_code)]
+  pub fn set_drag(&mut self, drag: Drag) {
+    for point in self.points.iter_mut() {
+      point.drag = drag;
+    }
+  }
+
+  #[allow(dead_code)]
+  pub fn set_springs(&mut self, groups: Vec<SpringGroup>) {
+    self.springs.clear();
+    self.hub_springs.clear();
+
+    for group in groups.iter() {
+      let springs = group.build(&self.frame.rest);
+
+      match group.topology {
+        SpringTopology::Hub => self.hub_springs.extend(springs),
+        _ => self.springs.extend(springs),
+      }
+    }
+  }
+
+  #[allow(dead_code)]
+  pub fn add_angle_springs(&mut self, strength: f32, damping: f32) {
+    let rest = &self.frame.rest;
+
+    for b in 0..self.np {
+      let a = (b + self.np - 1) % self.np;
+      let c = (b + 1) % self.np;
+      let angle = AngleSpring::angle(rest[a], rest[b], rest[c]);
+
+      self
+        .angle_springs
+        .push(AngleSpring::new(strength, angle, damping, a, b, c));
+    }
+  }
+
+  #[allow(dead_code)]
+  pub fn add_muscle(
+    &mut self,
+    a: usize,
+    b: usize,
+    strength: (f32, f32),
+    actuation: Actuation,
+  ) -> usize {
+    let length = self.frame.rest[a].distance(self.frame.rest[b]);
+    let spring = Spring::new(strength.0, length, strength.1, a, b);
+
+    self.muscles.push(Muscle::new(spring, actuation));
+    return self.muscles.len() - 1;
+  }
+
+  #[allow(dead_code)]
+  pub fn set_muscle_signal(&mut self, muscle: usize, signal: f32) {
+    self.muscles[muscle].signal = signal;
+  }
+
+  #[allow(dead_code)]
+  pub fn set_muscle_stiffness(&mut self, muscle: usize, stiffness: f32) {
+    self.muscles[muscle].stiffness = stiffness;
+  }
+
+  pub fn pin_point(&mut self, index: usize, anchor: Vec2) {
+    self.unpin_point(index);
+
+    self.points[index].locked = true;
+    self.points[index].velocity = Vec2::ZERO;
+    self.pins.push(Pin {
+      index,
+      anchor,
+      previous: anchor,
+    });
+    self.wake();
+  }
+
+  #[allow(dead_code)]
+  pub fn move_pin(&mut self, index: usize, anchor: Vec2) {
+    if let Some(pin) = self.pins.iter_mut().find(|pin| pin.index == index) {
+      pin.anchor = anchor;
+      self.wake();
+    }
+  }
+
+  pub fn unpin_point(&mut self, index: usize) {
+    if let Some(i) = self.pins.iter().position(|pin| pin.index == index) {
+      self.pins.remove(i);
+      self.points[index].locked = false;
+      self.wake();
+    }
+  }
+
+  // shapes collide when each one's category is in the other's mask
+  #[allow(dead_code)]
+  pub fn set_collision_filter(&mut self, category: u32, mask: u32) {
+    self.category = category;
+    self.mask = mask;
+  }
+
+  pub fn category(&self) -> u32 {
+    return self.category;
+  }
+
+  pub fn mask(&self) -> u32 {
+    return self.mask;
+  }
+
+  // sensors report overlaps through the world's sensor events instead of colliding. they are
+  // static: turning one on locks its frame, move it with set_target_transform, and the world
+  // leaves it out of gravity, fluids and fields
+  #[allow(dead_code)]
+  pub fn set_sensor(&mut self, sensor: bool) {
+    self.sensor = sensor;
+    if sensor {
+      self.lock_frame = true;
+      self.set_velocity(Vec2::ZERO);
+    }
+  }
+
+  pub fn is_sensor(&self) -> bool {
+    return self.sensor;
+  }
+
+  // only stop points coming from the side `direction` points to, when the contact normal is
+  // within `max_angle` of it
+  pub fn set_one_way(&mut self, one_way: Option<(Vec2, f32)>) {
+    self.one_way = one_way.map(|(direction, max_angle)| (direction.normalize(), max_angle));
+  }
+
+  pub fn one_way(&self) -> Option<(Vec2, f32)> {
+    return self.one_way;
+  }
+
+  pub fn set_drop_through(&mut self, drop_through: bool) {
     for point in self.points.iter_mut() {
-      point.velocity = velocity;
+      point.drop_through = drop_through;
+    }
+  }
+
+  pub fn set_submerged(&mut self, fraction: f32) {
+    self.submerged = fraction;
+  }
+
+  pub fn submerged(&self) -> f32 {
+    return self.submerged;
+  }
+
+  pub fn kinetic_energy(&self) -> f32 {
+    return self
+      .points
+      .iter()
+      .map(|p| 0.5 * p.mass * p.velocity.length_squared())
+      .sum();
+  }
+
+  pub fn is_kinematic(&self) -> bool {
+    return self.lock_frame;
+  }
+
+  pub fn set_target_transform(&mut self, position: Vec2, rotation: f32) {
+    self.lock_frame = true;
+    self.target = Some((position, rotation));
+    self.wake();
+  }
+
+  #[allow(dead_code)]
+  pub fn set_frame_velocity(&mut self, velocity: Vec2, angular_velocity: f32) {
+    self.lock_frame = true;
+    self.target = None;
+    self.frame_velocity = velocity;
+    self.angular_velocity = angular_velocity;
+    self.wake();
+  }
+
+  // hands a kinematic shape back to the simulation, the frame is fitted to the points again
+  #[allow(dead_code)]
+  pub fn set_dynamic(&mut self) {
+    self.lock_frame = false;
+    self.target = None;
+    self.frame_velocity = Vec2::ZERO;
+    self.angular_velocity = 0.0;
+    self.wake();
+  }
+
+  pub fn surface_velocity(&self, point: Vec2) -> Vec2 {
+    if !self.lock_frame {
+      return Vec2::ZERO;
     }
+
+    let r = point - self.frame.position;
+    return self.frame_velocity + self.angular_velocity * Vec2::new(-r.y, r.x);
   }
 
   pub fn update(&mut self, delta_time: f32) {
+    if self.sleeping {
+      return;
+    }
+
+    for pin in self.pins.iter_mut() {
+      let point = &mut self.points[pin.index];
+
+      point.velocity = (pin.anchor - pin.previous) / delta_time;
+      point.position = pin.anchor;
+      pin.previous = pin.anchor;
+    }
+
     for point in self.points.iter_mut() {
-      point.apply_gravity(GRAVITY * delta_time);
       point.update(delta_time);
     }
 
@@ -106,68 +575,131 @@ impl Shape {
       self.points[spring.b].apply_force(-force * delta_time);
     }
 
-    for i in 0..self.np {
-      let point = &mut self.points[i];
-
-      if point.velocity.length() != 0.0 {
-        let drag = DRAG_COEFFICIENT
-          * point.diameter
-          * point.velocity.length()
-          * point.velocity.length()
-          * -point.velocity.normalize();
-        point.apply_force(drag * delta_time);
+    if !self.hub_springs.is_empty() {
+      let mass = self.mass();
+      let mut hub = PointMass::new(self.center_of_mass(), mass, false);
+      hub.velocity = self
+        .points
+        .iter()
+        .fold(Vec2::ZERO, |total, p| total + p.velocity * p.mass)
+        / mass;
+
+      let mut reaction = Vec2::ZERO;
+      for spring in self.hub_springs.iter() {
+        let force = spring.calculate_force(&self.points[spring.a], &hub);
+
+        self.points[spring.a].apply_force(force * delta_time);
+        reaction -= force;
+      }
+
+      for point in self.points.iter_mut() {
+        point.apply_gravity(reaction / mass * delta_time);
       }
     }
 
+    self.time += delta_time;
+    for muscle in self.muscles.iter_mut() {
+      muscle.update(self.time);
+
+      let spring = &muscle.spring;
+      let force = spring.calculate_force(&self.points[spring.a], &self.points[spring.b]);
+
+      self.points[spring.a].apply_force(force * delta_time);
+      self.points[spring.b].apply_force(-force * delta_time);
+    }
+
+    for spring in self.angle_springs.iter() {
+      let (force_a, force_b, force_c) = spring.calculate_forces(
+        &self.points[spring.a],
+        &self.points[spring.b],
+        &self.points[spring.c],
+      );
+
+      self.points[spring.a].apply_force(force_a * delta_time);
+      self.points[spring.b].apply_force(force_b * delta_time);
+      self.points[spring.c].apply_force(force_c * delta_time);
+    }
+
+    for point in self.points.iter_mut() {
+      point.apply_drag(1.0 - self.submerged, delta_time);
+    }
+
     let mut min = Vec2::new(INFINITY, INFINITY);
     let mut max = Vec2::new(NEG_INFINITY, NEG_INFINITY);
 
-    let mut total_position = Vec2::ZERO;
-
     for point in self.points.iter() {
       min = min.min(point.position);
       max = max.max(point.position);
-
-      total_position += point.position;
     }
 
     self.bounding_box = (min, max);
 
     if !self.lock_frame {
-      self.position = total_position / self.np as f32;
-
-      let mut a = 0.0;
-      let mut b = 0.0;
-      for (i, v) in self.points.iter().enumerate() {
-        let r = v.position - self.position;
-        a += r.dot(self.frame[i]);
-        b += cross_2d(r, self.frame[i]);
-      }
-      let angle = -(b.atan2(a));
-
-      self.rotation = angle;
+      self.frame.fit(&self.points);
+    } else if let Some((position, rotation)) = self.target {
+      let turn = (rotation - self.frame.rotation + PI).rem_euclid(TAU) - PI;
+
+      self.frame_velocity = (position - self.frame.position) / delta_time;
+      self.angular_velocity = turn / delta_time;
+      self.frame.position = position;
+      self.frame.rotation = rotation;
+    } else {
+      self.frame.position += self.frame_velocity * delta_time;
+      self.frame.rotation += self.angular_velocity * delta_time;
     }
+    self.position = self.frame.position;
 
-    let angle_c = self.rotation.cos();
-    let angle_s = self.rotation.sin();
+    if self.lock_frame || self.clusters.is_empty() {
+      let goals = self.frame.goals(&self.points, self.matching);
+      for (i, goal) in goals.into_iter().enumerate() {
+        self.frame_points[i].position = goal;
+      }
+    } else {
+      let mut total_goal = vec![Vec2::ZERO; self.np];
+      let mut count = vec![0; self.np];
+
+      for cluster in self.clusters.iter_mut() {
+        cluster.fit(&self.points);
+
+        let goals = cluster.goals(&self.points, self.matching);
+        for (k, i) in cluster.indices.iter().enumerate() {
+          total_goal[*i] += goals[k];
+          count[*i] += 1;
+        }
+      }
 
-    for i in 0..self.np {
-      let frame_pos = self.frame[i];
-      self.frame_points[i].position = Vec2::new(
-        angle_c * frame_pos.x - angle_s * frame_pos.y + self.position.x,
-        angle_s * frame_pos.x + angle_c * frame_pos.y + self.position.y,
-      );
+      for i in 0..self.np {
+        self.frame_points[i].position = if count[i] > 0 {
+          total_goal[i] / count[i] as f32
+        } else {
+          self.points[i].position
+        };
+      }
     }
 
     for spring in self.frame_springs.iter() {
       let a = &mut self.points[spring.a];
       let force = spring.calculate_force(a, &self.frame_points[spring.b]);
-      a.velocity += force / a.mass * delta_time;
+      a.apply_force(force * delta_time);
     }
 
     let positions = self.points.iter().map(|p| p.position).collect();
 
     self.mesh.update_triangles(&positions);
+
+    let driven = !self.muscles.is_empty()
+      || self.lock_frame && (self.frame_velocity != Vec2::ZERO || self.angular_velocity != 0.0);
+
+    if self.can_sleep && !driven {
+      if self.kinetic_energy() / (self.np as f32) < SLEEP_ENERGY {
+        self.sleep_timer += delta_time;
+        if self.sleep_timer >= SLEEP_TIME {
+          self.sleep();
+        }
+      } else {
+        self.sleep_timer = 0.0;
+      }
+    }
   }
 
   pub fn draw(&self) {
@@ -181,11 +713,23 @@ impl Shape {
       self.frame_springs.iter().for_each(|spring| {
         spring.draw(&self.points[spring.a], &self.frame_points[spring.b]);
       });
+
+      self.muscles.iter().for_each(|muscle| {
+        let spring = &muscle.spring;
+        spring.draw(&self.points[spring.a], &self.points[spring.b]);
+      });
+
+      if !self.hub_springs.is_empty() {
+        let hub = PointMass::new(self.center_of_mass(), 0.0, false);
+        self.hub_springs.iter().for_each(|spring| {
+          spring.draw(&self.points[spring.a], &hub);
+        });
+      }
     }
 
     if DRAW_POINTS {
       self.points.iter().for_each(|point| {
-        point.draw();
+        draw_circle(point.position.x, point.position.y, 5.0, WHITE);
       });
     }
 
@@ -210,9 +754,23 @@ impl Shape {
       self.frame_points.iter().for_each(|point| {
         draw_circle_vec(point.position, 4.0, GRAY);
       });
+      self.clusters.iter().for_each(|cluster| {
+        draw_circle_vec(cluster.position, 3.0, ORANGE);
+      });
       draw_circle_vec(self.position, 5.0, RED);
     }
 
+    if DRAW_SLEEPING && self.is_sleeping() {
+      for i in 0..self.np {
+        draw_line_vec(
+          self.points[(i + self.np - 1) % self.np].position,
+          self.points[i].position,
+          2.0,
+          SKYBLUE,
+        );
+      }
+    }
+
     if DRAW_BOUNDING_BOX {
       draw_rectangle_lines(
         self.bounding_box.0.x,
@@ -225,38 +783,11 @@ impl Shape {
     }
   }
 
-  pub fn set_texture(&mut self, texture: Texture2D) {
-    self.mesh.set_texture(texture);
+  pub fn triangles(&self) -> Vec<[usize; 3]> {
+    return self.mesh.triangles();
   }
-}
 
-fn add_springs(
-  springs: &mut Vec<Spring>,
-  spacing: usize,
-  values: (f32, f32),
-  points: &Vec<PointMass>,
-) {
-  let np = points.len();
-
-  for i in 1..spacing + 1 {
-    springs.push(Spring::new(
-      values.0,
-      points[np - i]
-        .position
-        .distance(points[spacing - i].position),
-      values.1,
-      np - i,
-      spacing - i,
-    ));
-  }
-
-  for i in spacing..np {
-    springs.push(Spring::new(
-      values.0,
-      points[i - spacing].position.distance(points[i].position),
-      values.1,
-      i,
-      i - spacing,
-    ));
+  pub fn set_texture(&mut self, texture: Texture2D) {
+    self.mesh.set_texture(texture);
   }
 }
@@ -0,0 +1,86 @@
+use macroquad::prelude::*;
+
+use super::{triangulation_edges, Spring};
+
+#[allow(dead_code)]
+pub enum SpringTopology {
+  Neighbors(Vec<usize>),
+  Hub,
+  Radius(f32),
+  Mesh,
+  Pairs(Vec<(usize, usize)>),
+}
+
+pub struct SpringGroup {
+  pub topology: SpringTopology,
+  pub strength: f32,
+  pub damping: f32,
+}
+
+impl SpringGroup {
+  pub fn new(topology: SpringTopology, strength: f32, damping: f32) -> Self {
+    return Self {
+      topology,
+      strength,
+      damping,
+    };
+  }
+
+  // rest positions are relative to the center of mass, which is where hub springs attach
+  pub fn build(&self, rest: &Vec<Vec2>) -> Vec<Spring> {
+    let np = rest.len();
+    let mut springs = Vec::new();
+
+    let mut add_spring = |a: usize, b: usize| {
+      springs.push(Spring::new(
+        self.strength,
+        rest[a].distance(rest[b]),
+        self.damping,
+        a,
+        b,
+      ));
+    };
+
+    match &self.topology {
+      SpringTopology::Neighbors(spacings) => {
+        for spacing in spacings.iter() {
+          for i in 0..np {
+            add_spring(i, (i + spacing) % np);
+          }
+        }
+      }
+      SpringTopology::Hub => {
+        for i in 0..np {
+          springs.push(Spring::new(
+            self.strength,
+            rest[i].length(),
+            self.damping,
+            i,
+            i,
+          ));
+        }
+      }
+      SpringTopology::Radius(radius) => {
+        for a in 0..np {
+          for b in a + 1..np {
+            if rest[a].distance(rest[b]) <= *radius {
+              add_spring(a, b);
+            }
+          }
+        }
+      }
+      SpringTopology::Mesh => {
+        for (a, b) in triangulation_edges(rest) {
+          add_spring(a, b);
+        }
+      }
+      SpringTopology::Pairs(pairs) => {
+        for (a, b) in pairs.iter() {
+          add_spring(*a, *b);
+        }
+      }
+    }
+
+    return springs;
+  }
+}
@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::config::*;
+
+use super::*;
+
+pub struct World {
+  pub shapes: Vec<Shape>,
+  pub points: Vec<PointMass>,
+  pub fields: Vec<ForceField>,
+  pub fluids: Vec<Fluid>,
+  pub joints: Vec<Joint>,
+  // set to zero and add fields for things like radial gravity
+  pub gravity: Vec2,
+  pub colliders: Vec<Collider>,
+  pub particles: Particles,
+  filter: Option<Box<dyn Fn(Body, Body) -> bool>>,
+  overlaps: HashMap<(usize, Body), usize>,
+  sensor_events: Vec<SensorEvent>,
+  time: f32,
+}
+
+impl World {
+  pub fn new() -> Self {
+    return Self {
+      shapes: Vec::new(),
+      points: Vec::new(),
+      fields: Vec::new(),
+      fluids: Vec::new(),
+      joints: Vec::new(),
+      gravity: GRAVITY,
+      colliders: Vec::new(),
+      particles: Particles::new(),
+      filter: None,
+      overlaps: HashMap::new(),
+      sensor_events: Vec::new(),
+      time: 0.0,
+    };
+  }
+
+  pub fn add_shape(&mut self, shape: Shape) -> usize {
+    self.shapes.push(shape);
+    return self.shapes.len() - 1;
+  }
+
+  pub fn add_point(&mut self, point: PointMass) -> usize {
+    self.points.push(point);
+    return self.points.len() - 1;
+  }
+
+  pub fn add_field(&mut self, field: ForceField) {
+    self.fields.push(field);
+  }
+
+  pub fn add_fluid(&mut self, fluid: Fluid) {
+    self.fluids.push(fluid);
+  }
+
+  pub fn add_joint(&mut self, a: Anchor, b: Anchor, kind: JointKind) -> usize {
+    self.joints.push(Joint::new(a, b, kind, &self.shapes));
+    return self.joints.len() - 1;
+  }
+
+  pub fn add_collider(&mut self, collider: Collider) -> usize {
+    self.colliders.push(collider);
+    return self.colliders.len() - 1;
+  }
+
+  // return false from the filter to stop a pair of bodies from colliding
+  #[allow(dead_code)]
+  pub fn set_filter(&mut self, filter: impl Fn(Body, Body) -> bool + 'static) {
+    self.filter = Some(Box::new(filter));
+  }
+
+  pub fn should_collide(&self, a: Body, b: Body) -> bool {
+    let (category_a, mask_a) = self.collision_bits(a);
+    let (category_b, mask_b) = self.collision_bits(b);
+
+    if category_a & mask_b == 0 || category_b & mask_a == 0 {
+      return false;
+    }
+
+    return match &self.filter {
+      Some(filter) => filter(a, b),
+      None => true,
+    };
+  }
+
+  fn collision_bits(&self, body: Body) -> (u32, u32) {
+    return match body {
+      Body::Shape(s) => (self.shapes[s].category(), self.shapes[s].mask()),
+      Body::Point(p) => (self.points[p].category, self.points[p].mask),
+    };
+  }
+
+  // sweeps the shape bounding boxes along x and returns the overlapping pairs that are
+  // allowed to collide
+  pub fn broad_phase(&self) -> Vec<(usize, usize)> {
+    let mut order = (0..self.shapes.len()).collect::<Vec<usize>>();
+    order.sort_by(|a, b| {
+      let a = self.shapes[*a].bounding_box.0.x;
+      let b = self.shapes[*b].bounding_box.0.x;
+      a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut pairs = Vec::new();
+    for (k, a) in order.iter().enumerate() {
+      let (min_a, max_a) = self.shapes[*a].bounding_box;
+
+      for b in order[k + 1..].iter() {
+        let (min_b, max_b) = self.shapes[*b].bounding_box;
+        if min_b.x > max_a.x {
+          break;
+        }
+        if min_b.y > max_a.y || max_b.y < min_a.y {
+          continue;
+        }
+        // sensors keep checking sleeping bodies so they don't report a false exit
+        let sensor = self.shapes[*a].is_sensor() || self.shapes[*b].is_sensor();
+        if !sensor && self.shapes[*a].is_sleeping() && self.shapes[*b].is_sleeping() {
+          continue;
+        }
+        if !self.should_collide(Body::Shape(*a), Body::Shape(*b)) {
+          continue;
+        }
+
+        pairs.push((*a.min(b), *a.max(b)));
+      }
+    }
+
+    return pairs;
+  }
+
+  fn point_pairs(&self) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+
+    for (p, point) in self.points.iter().enumerate() {
+      for (s, shape) in self.shapes.iter().enumerate() {
+        let (min, max) = shape.bounding_box;
+        let position = point.position;
+        let radius = point.radius();
+        if position.x < min.x - radius
+          || position.y < min.y - radius
+          || position.x > max.x + radius
+          || position.y > max.y + radius
+        {
+          continue;
+        }
+
+        if self.should_collide(Body::Point(p), Body::Shape(s)) {
+          pairs.push((p, s));
+        }
+      }
+    }
+
+    return pairs;
+  }
+
+  // closest hit along the ray against shapes whose category is in `mask`, sensors are ignored
+  pub fn raycast(
+    &self,
+    origin: Vec2,
+    direction: Vec2,
+    max_distance: f32,
+    mask: u32,
+  ) -> Option<RayHit> {
+    return self
+      .raycast_all(origin, direction, max_distance, mask)
+      .into_iter()
+      .next();
+  }
+
+  // every edge the ray crosses, going in and out of each shape, nearest first
+  pub fn raycast_all(
+    &self,
+    origin: Vec2,
+    direction: Vec2,
+    max_distance: f32,
+    mask: u32,
+  ) -> Vec<RayHit> {
+    let end = origin + direction.normalize_or_zero() * max_distance;
+
+    return self.cast(origin, end, mask, |shape| raycast_shape(shape, origin, end));
+  }
+
+  // like a raycast, but for a circle of `radius` moving along the ray
+  #[allow(dead_code)]
+  pub fn circle_cast(
+    &self,
+    origin: Vec2,
+    direction: Vec2,
+    radius: f32,
+    max_distance: f32,
+    mask: u32,
+  ) -> Option<RayHit> {
+    let end = origin + direction.normalize_or_zero() * max_distance;
+
+    let hits = self.cast(origin, end, mask, |shape| {
+      circle_cast_shape(shape, origin, end, radius)
+        .into_iter()
+        .collect()
+    });
+
+    return hits.into_iter().next().map(|hit| RayHit {
+      point: hit.point - hit.normal * radius,
+      ..hit
+    });
+  }
+
+  fn cast(
+    &self,
+    origin: Vec2,
+    end: Vec2,
+    mask: u32,
+    test: impl Fn(&Shape) -> Vec<((usize, usize), f32, Vec2)>,
+  ) -> Vec<RayHit> {
+    let mut hits = Vec::new();
+
+    for (s, shape) in self.shapes.iter().enumerate() {
+      if shape.is_sensor() || shape.category() & mask == 0 {
+        continue;
+      }
+
+      for (edge, fraction, normal) in test(shape) {
+        hits.push(RayHit {
+          shape: s,
+          edge,
+          point: lerp_vec(fraction, origin, end),
+          normal,
+          distance: fraction * origin.distance(end),
+        });
+      }
+    }
+
+    hits.sort_by(|a, b| {
+      a.distance
+        .partial_cmp(&b.distance)
+        .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    return hits;
+  }
+
+  // the topmost shape whose outline contains the position
+  #[allow(dead_code)]
+  pub fn shape_at(&self, position: Vec2) -> Option<usize> {
+    return (0..self.shapes.len())
+      .rev()
+      .find(|s| point_shape_collision(position, &self.shapes[*s]).is_some());
+  }
+
+  pub fn nearest_point(&self, position: Vec2, radius: f32) -> Option<Anchor> {
+    let mut closest = radius;
+    let mut nearest = None;
+
+    for anchor in self.points_within(position, radius) {
+      let distance = anchor
+        .position(&self.shapes, &self.points)
+        .distance(position);
+      if distance <= closest {
+        closest = distance;
+        nearest = Some(anchor);
+      }
+    }
+
+    return nearest;
+  }
+
+  // every shape point and free point within `radius`
+  pub fn points_within(&self, position: Vec2, radius: f32) -> Vec<Anchor> {
+    let mut anchors = Vec::new();
+    let offset = Vec2::splat(radius);
+
+    for body in self.query_aabb(position - offset, position + offset) {
+      match body {
+        Body::Shape(s) => {
+          for (i, point) in self.shapes[s].points.iter().enumerate() {
+            if point.position.distance(position) <= radius {
+              anchors.push(Anchor::vertex(s, i));
+            }
+          }
+        }
+        Body::Point(i) => {
+          if self.points[i].position.distance(position) <= radius {
+            anchors.push(Anchor::Point(i));
+          }
+        }
+      }
+    }
+
+    return anchors;
+  }
+
+  // the closest spot on any shape outline within `radius`, as an anchor on that edge
+  #[allow(dead_code)]
+  pub fn nearest_edge(&self, position: Vec2, radius: f32) -> Option<Anchor> {
+    let offset = Vec2::splat(radius);
+    let mut closest = radius;
+    let mut nearest = None;
+
+    for body in self.query_aabb(position - offset, position + offset) {
+      let s = match body {
+        Body::Shape(s) => s,
+        Body::Point(_) => continue,
+      };
+
+      let points = &self.shapes[s].points;
+      let mut prev = points.len() - 1;
+      for i in 0..points.len() {
+        let (point, t) =
+          closest_point_on_segment(position, points[prev].position, points[i].position);
+
+        if point.distance(position) <= closest {
+          closest = point.distance(position);
+          nearest = Some(Anchor::edge(s, prev, i, t));
+        }
+
+        prev = i;
+      }
+    }
+
+    return nearest;
+  }
+
+  // shapes whose bounding box overlaps the box, and free points inside it
+  pub fn query_aabb(&self, min: Vec2, max: Vec2) -> Vec<Body> {
+    let mut bodies = Vec::new();
+
+    for (s, shape) in self.shapes.iter().enumerate() {
+      let (shape_min, shape_max) = shape.bounding_box;
+      if shape_max.x < min.x || shape_max.y < min.y || shape_min.x > max.x || shape_min.y > max.y {
+        continue;
+      }
+
+      bodies.push(Body::Shape(s));
+    }
+
+    for (i, point) in self.points.iter().enumerate() {
+      let p = point.position;
+      if p.x >= min.x && p.y >= min.y && p.x <= max.x && p.y <= max.y {
+        bodies.push(Body::Point(i));
+      }
+    }
+
+    return bodies;
+  }
+
+  pub fn update(&mut self, delta_time: f32) {
+    self.time += delta_time;
+
+    for shape in self.shapes.iter_mut() {
+      if shape.is_kinematic() || shape.is_sensor() {
+        continue;
+      }
+
+      // a shape resting in or under water stays asleep until it goes in or the surface moves
+      if shape.is_sleeping() {
+        let submerged: f32 = self
+          .fluids
+          .iter()
+          .map(|fluid| fluid.submerged_fraction(shape, self.time))
+          .sum();
+        if (submerged.min(1.0) - shape.submerged()).abs() < SLEEP_SUBMERGED {
+          continue;
+        }
+        shape.wake();
+      }
+
+      let mut submerged = 0.0;
+      for fluid in self.fluids.iter() {
+        submerged += fluid.apply(shape, self.gravity, self.time, delta_time);
+      }
+      shape.set_submerged(submerged.min(1.0));
+    }
+
+    for field in self.fields.iter() {
+      for shape in self.shapes.iter_mut() {
+        if shape.is_sensor() || !shape.points.iter().any(|p| field.in_range(p.position)) {
+          continue;
+        }
+
+        shape.wake();
+        for point in shape.points.iter_mut() {
+          field.apply(point, self.time, delta_time);
+        }
+      }
+
+      for point in self.points.iter_mut() {
+        field.apply(point, self.time, delta_time);
+      }
+    }
+    self.fields.retain(|field| !field.is_one_shot());
+
+    for point in self.points.iter_mut() {
+      point.apply_gravity(point.gravity(self.gravity) * delta_time);
+      point.apply_drag(1.0, delta_time);
+      point.update(delta_time);
+    }
+
+    for shape in self.shapes.iter_mut() {
+      shape.apply_gravity(self.gravity, delta_time);
+      shape.update(delta_time);
+    }
+
+    for joint in self.joints.iter() {
+      joint.solve(&mut self.shapes, &mut self.points, delta_time);
+    }
+
+    let mut overlaps = HashMap::new();
+
+    for (a, b) in self.broad_phase() {
+      if self.shapes[a].is_sensor() || self.shapes[b].is_sensor() {
+        for (sensor, other) in [(a, b), (b, a)] {
+          if !self.shapes[sensor].is_sensor() {
+            continue;
+          }
+
+          let inside = points_inside(&self.shapes[sensor], &self.shapes[other].points);
+          if inside > 0 {
+            overlaps.insert((sensor, Body::Shape(other)), inside);
+          }
+        }
+        continue;
+      }
+
+      let (shape_a, shape_b) = pair_mut(&mut self.shapes, a, b);
+
+      shape_shape_collision(shape_a, shape_b);
+      shape_shape_collision(shape_b, shape_a);
+      edge_edge_collision(shape_a, shape_b);
+    }
+
+    for a in 0..self.points.len() {
+      for b in a + 1..self.points.len() {
+        if !self.should_collide(Body::Point(a), Body::Point(b)) {
+          continue;
+        }
+
+        let (point_a, point_b) = pair_mut(&mut self.points, a, b);
+        point_point_collision(point_a, point_b);
+      }
+    }
+
+    for (p, s) in self.point_pairs() {
+      let point = &mut self.points[p];
+      let shape = &mut self.shapes[s];
+
+      if let Some(collision) = circle_shape_collision(point, shape) {
+        if shape.is_sensor() {
+          overlaps.insert((s, Body::Point(p)), 1);
+        } else {
+          resolve_point_line(point, shape, collision);
+        }
+      }
+    }
+
+    for collider in self.colliders.iter() {
+      let (min, max) = collider.bounds();
+
+      for shape in self.shapes.iter_mut() {
+        let (shape_min, shape_max) = shape.bounding_box;
+        if shape.is_sensor()
+          || shape.is_sleeping()
+          || shape_max.x < min.x
+          || shape_max.y < min.y
+          || shape_min.x > max.x
+          || shape_min.y > max.y
+        {
+          continue;
+        }
+
+        for point in shape.points.iter_mut() {
+          if let Some((normal, depth)) = collider.contact(point, 0.0) {
+            resolve_point_static(point, normal, depth);
+          }
+        }
+      }
+
+      for point in self.points.iter_mut() {
+        if let Some((normal, depth)) = collider.contact(point, point.radius()) {
+          resolve_point_static(point, normal, depth);
+        }
+      }
+    }
+
+    self
+      .particles
+      .update(&mut self.shapes, &self.colliders, self.gravity, delta_time);
+
+    self.update_sensors(overlaps);
+  }
+
+  fn update_sensors(&mut self, overlaps: HashMap<(usize, Body), usize>) {
+    self.sensor_events.clear();
+
+    for ((sensor, body), points_inside) in overlaps.iter() {
+      if !self.overlaps.contains_key(&(*sensor, *body)) {
+        self.sensor_events.push(SensorEvent {
+          sensor: *sensor,
+          body: *body,
+          kind: SensorEventKind::Enter,
+          points_inside: *points_inside,
+        });
+      }
+    }
+
+    for ((sensor, body), points_inside) in self.overlaps.iter() {
+      if !overlaps.contains_key(&(*sensor, *body)) {
+        self.sensor_events.push(SensorEvent {
+          sensor: *sensor,
+          body: *body,
+          kind: SensorEventKind::Exit,
+          points_inside: *points_inside,
+        });
+      }
+    }
+
+    self.overlaps = overlaps;
+  }
+
+  // events from the last update
+  #[allow(dead_code)]
+  pub fn sensor_events(&self) -> &Vec<SensorEvent> {
+    return &self.sensor_events;
+  }
+
+  // how many points of `body` are currently inside the sensor
+  #[allow(dead_code)]
+  pub fn overlap(&self, sensor: usize, body: Body) -> usize {
+    return *self.overlaps.get(&(sensor, body)).unwrap_or(&0);
+  }
+
+  pub fn draw(&self) {
+    for fluid in self.fluids.iter() {
+      fluid.draw(self.time);
+    }
+
+    for collider in self.colliders.iter() {
+      collider.draw();
+    }
+
+    for shape in self.shapes.iter() {
+      shape.draw();
+    }
+
+    for point in self.points.iter() {
+      point.draw();
+    }
+
+    self.particles.draw();
+
+    if DRAW_JOINTS {
+      for joint in self.joints.iter() {
+        let a = joint.a.position(&self.shapes, &self.points);
+        let b = joint.b.position(&self.shapes, &self.points);
+
+        draw_line_vec(a, b, 2.0, YELLOW);
+        draw_circle_vec(a, 3.0, YELLOW);
+        draw_circle_vec(b, 3.0, YELLOW);
+      }
+    }
+  }
+}
+
+fn pair_mut<T>(items: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+  if a < b {
+    let (left, right) = items.split_at_mut(b);
+    return (&mut left[a], &mut right[0]);
+  }
+
+  let (left, right) = items.split_at_mut(a);
+  return (&mut right[0], &mut left[b]);
+}
@@ -28,27 +28,82 @@ async fn main() {
   let platform_points: Vec<(Vec2, f32)> = object_data::PLATFORM_POINTS.into();
   let shape_points: Vec<(Vec2, f32)> = object_data::_SKRUNGLE_POINTS.into();
 
+  let mut world = World::new();
+
   let p_mass = 1.0;
-  let mut point = PointMass::new(Vec2::new(200.0, 200.0), p_mass, false);
-  let mut shape = Shape::new(shape_points, (500.0, 30.0), (1500.0, 0.0), false, 0.15);
-  let mut platform = Shape::new(platform_points, (800.0, 30.0), (1000.0, 10.0), true, 1.0);
+  let point = world.add_point(PointMass::new(Vec2::new(200.0, 200.0), p_mass, false));
+  world.points[point].set_radius(5.0);
+  let shape = world.add_shape(Shape::new(
+    shape_points,
+    (500.0, 30.0),
+    (1500.0, 0.0),
+    false,
+    0.15,
+  ));
+  let platform = world.add_shape(Shape::new(
+    platform_points,
+    (800.0, 30.0),
+    (1000.0, 10.0),
+    true,
+    1.0,
+  ));
+
+  // a ledge above the skrungle that it can jump up through, hold down to drop back through it
+  let mut ledge = Shape::new(
+    vec![
+      (Vec2::new(0.0, -50.0), 1.0),
+      (Vec2::new(140.0, -50.0), 1.0),
+      (Vec2::new(140.0, -40.0), 1.0),
+      (Vec2::new(0.0, -40.0), 1.0),
+    ],
+    (800.0, 30.0),
+    (1000.0, 10.0),
+    true,
+    1.0,
+  );
+  ledge.set_one_way(Some((Vec2::new(0.0, -1.0), 1.0)));
+  world.add_shape(ledge);
+
+  world.add_collider(Collider::Chain(vec![
+    Vec2::new(676.0, 450.0),
+    Vec2::new(850.0, 520.0),
+    Vec2::new(1050.0, 520.0),
+    Vec2::new(1150.0, 420.0),
+  ]));
+  world.add_collider(Collider::Circle(Vec2::new(950.0, 520.0), 30.0));
 
   // let texture =
   //   Texture2D::from_file_with_format(include_bytes!("../texture.png"), Some(ImageFormat::Png));
   let texture = load_texture("src/texture.png").await.unwrap();
-  shape.set_texture(texture.clone());
+  world.shapes[shape].set_texture(texture.clone());
 
   let mut drawing = false;
   let mut drawing_points = Vec::new();
 
   let mut direction = Vec2::ZERO;
+  let mut matching_mode = 0;
+  let mut clustered = false;
+  let mut swinging = false;
+  let mut flooded = false;
+  let mut hanging = false;
+  let mut raining = false;
+  let mut rain = Emitter::new(Vec2::ZERO, Vec2::new(0.0, 1.0), 300.0, SKYBLUE);
+  rain.speed = (200.0, 300.0);
+  rain.lifetime = (3.0, 4.0);
+  rain.diameter = (2.0, 4.0);
+  rain.width = 800.0;
+  let platform_start = world.shapes[platform].position;
+  let mut time: f32 = 0.0;
 
   let aspect_ratio = screen_width() / screen_height();
   let scale = 500.0 / ZOOM;
+  let mut camera = Camera2D::default();
+  let mut grab = Grab::new(2000.0, 40.0);
 
   loop {
     // let delta_time = get_frame_time();
     let delta_time = 0.007;
+    time += delta_time;
 
     if is_key_pressed(KeyCode::D) {
       drawing = !drawing;
@@ -75,7 +130,7 @@ async fn main() {
     } else {
       if is_key_down(KeyCode::A) {
         let mouse_pos: Vec2 = mouse_position().into();
-        point = PointMass::new(mouse_pos, p_mass, false);
+        world.points[point] = PointMass::new(mouse_pos, p_mass, false);
       }
 
       let mut new_dir = Vec2::ZERO;
@@ -93,34 +148,150 @@ async fn main() {
       if new_dir.length() > 0.0 {
         direction = new_dir;
       }
+      world.shapes[shape].set_drop_through(is_key_down(KeyCode::Down));
 
-      draw_line_vec(shape.position, shape.position + direction * 25.0, 3.0, BLUE);
-      if is_key_pressed(KeyCode::X) {
-        shape.set_velocity(Vec2::ZERO);
-        shape.apply_force(direction * 1500.0);
+      if is_key_pressed(KeyCode::M) {
+        matching_mode = (matching_mode + 1) % 3;
+        world.shapes[shape].set_matching_mode(match matching_mode {
+          1 => MatchingMode::Linear(0.5),
+          2 => MatchingMode::Quadratic(0.5),
+          _ => MatchingMode::Rigid,
+        });
       }
 
-      point.apply_gravity(GRAVITY * delta_time);
+      if is_key_pressed(KeyCode::P) {
+        swinging = !swinging;
+        if !swinging {
+          world.shapes[platform].set_target_transform(platform_start, 0.0);
+        }
+      }
 
-      shape.update(delta_time);
-      platform.update(delta_time);
-      point.update(delta_time);
+      if swinging {
+        world.shapes[platform].set_target_transform(
+          platform_start + Vec2::new(100.0 * (time * 0.8).sin(), 0.0),
+          0.15 * (time * 1.3).sin(),
+        );
+      }
 
-      shape_shape_collision(&mut shape, &mut platform);
-      if let Some(collision) = point_shape_collision(point.position, &platform) {
-        resolve_point_line(&mut point, &mut platform, collision);
+      if is_key_pressed(KeyCode::F) {
+        flooded = !flooded;
+        if flooded {
+          let position = world.shapes[shape].position;
+          let mut water = Fluid::new(
+            FluidArea::Box(
+              position + Vec2::new(-300.0, -10.0),
+              position + Vec2::new(300.0, 300.0),
+            ),
+            0.03,
+            2.0,
+            2.0,
+          );
+          water.set_surface(|x, time| 8.0 + 6.0 * (x * 0.03 + time * 2.0).sin());
+          world.add_fluid(water);
+        } else {
+          world.fluids.clear();
+        }
+      }
+
+      if is_key_pressed(KeyCode::J) {
+        if world.joints.is_empty() {
+          world.add_joint(
+            Anchor::Point(point),
+            Anchor::vertex(shape, 0),
+            JointKind::Distance {
+              min: 0.0,
+              max: 60.0,
+            },
+          );
+        } else {
+          world.joints.clear();
+        }
+      }
+
+      if is_key_pressed(KeyCode::H) {
+        hanging = !hanging;
+        let body = &mut world.shapes[shape];
+        if hanging {
+          let anchor = body.points[0].position;
+          body.pin_point(0, anchor);
+        } else {
+          body.unpin_point(0);
+        }
+      }
+
+      if is_key_pressed(KeyCode::C) {
+        clustered = !clustered;
+        if clustered {
+          world.shapes[shape].generate_clusters(25.0);
+        } else {
+          world.shapes[shape].set_clusters(Vec::new());
+        }
       }
 
-      set_camera(&Camera2D {
+      let mouse_world = camera.screen_to_world(mouse_position().into());
+      if is_mouse_button_pressed(MouseButton::Left) {
+        grab.start(&world, mouse_world, 30.0);
+      }
+      if is_mouse_button_down(MouseButton::Left) {
+        grab.drag(mouse_world);
+      }
+      if is_mouse_button_released(MouseButton::Left) {
+        grab.release();
+      }
+      grab.apply(&mut world, delta_time);
+
+      if is_key_pressed(KeyCode::R) {
+        raining = !raining;
+      }
+      if raining {
+        rain.position = world.shapes[shape].position - Vec2::new(0.0, 400.0);
+        rain.emit(&mut world.particles, delta_time);
+      }
+
+      if is_key_pressed(KeyCode::E) {
+        world.add_field(ForceField::new(
+          FieldKind::Explosion {
+            center: mouse_world,
+            impulse: 400.0,
+            radius: 150.0,
+          },
+          None,
+        ));
+      }
+
+      let position = world.shapes[shape].position;
+      draw_line_vec(position, position + direction * 25.0, 3.0, BLUE);
+      if is_key_pressed(KeyCode::X) {
+        world.shapes[shape].set_velocity(Vec2::ZERO);
+        world.shapes[shape].apply_force(direction * 1500.0);
+      }
+
+      world.update(delta_time);
+
+      let position = world.shapes[shape].position;
+      camera = Camera2D {
         zoom: vec2(1.0, aspect_ratio) / scale,
-        offset: vec2(-shape.position.x, shape.position.y * aspect_ratio) / scale,
+        offset: vec2(-position.x, position.y * aspect_ratio) / scale,
         ..Default::default()
-      });
+      };
+      set_camera(&camera);
 
       next_frame().await;
-      shape.draw();
-      platform.draw();
-      point.draw();
+      world.draw();
+      grab.draw(&world);
+
+      if is_key_down(KeyCode::L) {
+        let origin = world.points[point].position;
+        let direction = mouse_world - origin;
+        let end = match world.raycast(origin, direction, 1000.0, u32::MAX) {
+          Some(hit) => {
+            draw_line_vec(hit.point, hit.point + hit.normal * 15.0, 2.0, GREEN);
+            hit.point
+          }
+          None => origin + direction.normalize_or_zero() * 1000.0,
+        };
+        draw_line_vec(origin, end, 2.0, RED);
+      }
     }
   }
 }